@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::f32::consts::FRAC_PI_4;
 use std::f32::consts::PI;
 
@@ -5,7 +6,7 @@ use nalgebra as na;
 use ggez::graphics::Color;
 use rand::RngCore;
 
-use neural_network::{Network, LayerTopology};
+use neural_network::{Activation, Initializer, Network, NetworkState, LayerKind, LayerTopology, Scratch, WeightInit};
 use crate::eye::*;
 use crate::food::Food;
 
@@ -41,57 +42,80 @@ pub const CREATURE_EYE_CELLS: usize = 9;
 pub const CREATURE_EYE_ANGLE: f32 = PI + FRAC_PI_4;
 pub const CREATURE_EYE_RANGE: f32 = 1000.0; // CREATURE_SIZE * 25.0;
 
+const COVERAGE_CELL_SIZE: f32 = CREATURE_SIZE * 5.0; // grid used to measure how much ground a creature covered
+const TURN_BINS: usize = 5; // hard left .. straight .. hard right
+
+// Shared by fresh creatures, evolved children and the crossover's neuron blocks
+pub fn brain_topology() -> [LayerTopology; 4] {
+    [ // 5 3
+        LayerTopology {num_neuron: CREATURE_EYE_CELLS, activation: Activation::Relu, kind: LayerKind::Dense},
+        LayerTopology {num_neuron: 5, activation: Activation::Relu, kind: LayerKind::Dense},
+        LayerTopology {num_neuron: 3, activation: Activation::Tanh, kind: LayerKind::Elman}, // remembers food that just left the field of view
+        LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Dense}, // signed, so it can turn left and brake
+    ]
+}
+
 pub struct Creature {
     pub position: na::Point2<f32>,
     pub rotation: f32, // radians // clockwise, start from south
     pub speed: f32,
     pub eat: u32,
+    pub distance: f32, // travelled since spawning
+    visited: HashSet<(i32, i32)>, // coverage cells
+    turns: [u32; TURN_BINS],
     pub color: Color,
     pub eye: Eye,
     pub brain: Network,
+    scratch: Scratch,
+    state: NetworkState,
 }
 
 impl Creature {
     pub fn new(position: na::Point2<f32>, rotation: f32, speed: f32, rng: &mut dyn RngCore, optional_brain: Option<Network>) -> Self {
         let eye = Eye::new(CREATURE_EYE_RANGE, CREATURE_EYE_ANGLE, CREATURE_EYE_CELLS);
-        let cells = eye.cells();
+        debug_assert_eq!(eye.cells(), brain_topology()[0].num_neuron);
         let brain = if let Some(brain) = optional_brain {
             brain
         } else {
-            Network::random(rng, &[ // 5 3 
-                LayerTopology {num_neuron: cells}, // how to use eye.cells() here?
-                LayerTopology {num_neuron: 5},
-                LayerTopology {num_neuron: 3},
-                LayerTopology {num_neuron: 2},
-            ])
+            // scaled by fan-in, so the wide eye layer does not saturate from the first generation
+            Network::random_with(rng, &brain_topology(), Initializer::new(WeightInit::Xavier))
         };
+        let scratch = Scratch::for_network(&brain);
+        let state = NetworkState::for_network(&brain); // every spawn starts with a blank memory
 
         Self { 
             position, 
             rotation, 
             speed, 
             eat: 0, 
+            distance: 0.0,
+            visited: HashSet::new(),
+            turns: [0; TURN_BINS],
             color: Color::WHITE,
             eye,
             brain,
+            scratch,
+            state,
         }
     }
 
     pub fn move_for_foods(&mut self, foods: &Vec<Food>) {
         let vision_info = self.see(foods);
         let actions = self.decide(vision_info);
-        self.move_body(actions);
+        self.move_body(&actions);
     }
 
     fn see(&self, foods: &Vec<Food>) -> Vec<f32> {
         self.eye.process_vision(self.position, self.rotation, foods)
     }
 
-    fn decide(&self, vision_info: Vec<f32>) -> Vec<f32> {
-        self.brain.propagate(vision_info)
+    fn decide(&mut self, vision_info: Vec<f32>) -> [f32; 2] {
+        let mut actions = [0.0; 2];
+        self.brain.propagate_stateful(&vision_info, &mut actions, &mut self.scratch, &mut self.state);
+        actions
     }
 
-    fn move_body(&mut self, actions: Vec<f32>) {
+    fn move_body(&mut self, actions: &[f32]) {
         let rotation_chage = actions[0].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
         let speed_change = actions[1].clamp(-SPEED_ACCEL, SPEED_ACCEL);
 
@@ -105,6 +129,13 @@ impl Creature {
 
         self.position.x += dx;
         self.position.y += dy;
+        self.distance += self.speed;
+
+        let column = (self.position.x / COVERAGE_CELL_SIZE).floor() as i32;
+        let row = (self.position.y / COVERAGE_CELL_SIZE).floor() as i32;
+        self.visited.insert((column, row));
+        let bin = (rotation_chage + ROTATION_ACCEL) / (2.0 * ROTATION_ACCEL) * TURN_BINS as f32;
+        self.turns[(bin as usize).min(TURN_BINS - 1)] += 1;
     }
 
     pub fn eat(&mut self, food: &Food) -> bool {
@@ -121,4 +152,16 @@ impl Creature {
             false
         }
     }
-}
\ No newline at end of file
+
+    // What the creature did, for novelty search: final position, cells covered and how often it turned each way
+    pub fn behaviour(&self) -> Vec<f32> {
+        let steps = self.turns.iter().sum::<u32>().max(1) as f32;
+        let mut behaviour = vec![
+            self.position.x / COVERAGE_CELL_SIZE,
+            self.position.y / COVERAGE_CELL_SIZE,
+            self.visited.len() as f32,
+        ];
+        behaviour.extend(self.turns.iter().map(|&count| count as f32 / steps));
+        behaviour
+    }
+}
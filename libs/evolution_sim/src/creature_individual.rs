@@ -1,11 +1,13 @@
-use genetic_algorithm::{Chromosome, Individual};
-use neural_network::{LayerTopology, Network};
+use genetic_algorithm::{Behaviour, Chromosome, Individual, MultiObjective};
+use neural_network::Network;
 
-use crate::{Creature, CREATURE_EYE_CELLS};
+use crate::{brain_topology, Creature};
 
 pub struct CreatureIndividual {
     chromosome: Chromosome,
     fitness: f32,
+    distance: f32,
+    behaviour: Vec<f32>,
 }
 
 
@@ -15,17 +17,14 @@ impl CreatureIndividual {
         Self {
             chromosome: Chromosome::from_iter(creature.brain.get_params()),
             fitness: creature.eat as f32,
+            distance: creature.distance,
+            behaviour: creature.behaviour(),
         }
     }
 
     pub fn into_brain(&self) -> Network {
         let brain = Network::from_params(
-            &[ // 5 3
-                LayerTopology {num_neuron: CREATURE_EYE_CELLS}, // 13
-                LayerTopology {num_neuron: 5},
-                LayerTopology {num_neuron: 3},
-                LayerTopology {num_neuron: 2},
-            ], 
+            &brain_topology(),
             self.chromosome.clone().into_iter(),
         );
 
@@ -34,6 +33,8 @@ impl CreatureIndividual {
 }
 
 impl Individual for CreatureIndividual {
+    type Gene = f32;
+
     fn chromosome(&self) -> &Chromosome {
         &self.chromosome
     }
@@ -46,6 +47,21 @@ impl Individual for CreatureIndividual {
         Self {
             chromosome,
             fitness: 0.0,
+            distance: 0.0,
+            behaviour: Vec::new(),
         }
     }
-}
\ No newline at end of file
+}
+
+// Food eaten against distance travelled, for `Nsga2`: the front shows how much food each extra bit of movement buys
+impl MultiObjective for CreatureIndividual {
+    fn objectives(&self) -> Vec<f32> {
+        vec![self.fitness, -self.distance]
+    }
+}
+
+impl Behaviour for CreatureIndividual {
+    fn behaviour(&self) -> Vec<f32> {
+        self.behaviour.clone()
+    }
+}
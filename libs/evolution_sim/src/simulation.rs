@@ -1,17 +1,22 @@
 use genetic_algorithm::*;
 use neural_network::Network;
-use crate::{world::*, creature_individual::*};
+use crate::{brain_topology, world::*, creature_individual::*};
 
 use rand_chacha::ChaCha8Rng;
 use rand::SeedableRng;
 
 const MAX_GENERATION_AGE: i32 = 3500;
 pub const POPULATION_SIZE: i32 = 20;
+const ISLAND_COUNT: usize = 4; // creatures share one world but only breed within their island
+const MAX_STAGNANT_GENERATIONS: usize = 300; // reported once the best creature has not improved for this long
+const STOP_ON_STAGNATION: bool = false; // long plateaus are common and often end, so by default only log them
 
 const FOOD_NUMBER: i32 = 20;
 pub struct Simulation {
     pub world: World,
-    genetic_algorithm: GenericAlgorithm::<RouletteWheelSelection>,
+    genetic_algorithm: IslandModel::<TournamentSelection>,
+    evolver: Evolver<'static, CreatureIndividual>,
+    stalled: bool, // the evolver currently reports stagnation; with STOP_ON_STAGNATION the last world keeps running
     pub width: f32,
     pub height: f32,
     evolution_epoch: i32,
@@ -21,11 +26,18 @@ impl Simulation {
     pub fn new(width: f32, height: f32) -> Self {
         Self {
             world: World::new(width, height, None, FOOD_NUMBER),
-            genetic_algorithm: GenericAlgorithm::new(
-                RouletteWheelSelection, 
-                UniformCrossover, 
-                GussianMutation::new(0.01, 0.2)
-            ),
+            genetic_algorithm: IslandModel::new(
+                (0..ISLAND_COUNT).map(|_| GenericAlgorithm::new(
+                    TournamentSelection::new(2, 1.0), // only the order of the food counts matters, not their scale
+                    NeuronAlignedCrossover::new(Network::neuron_blocks(&brain_topology())), // keeps each neuron's weights together
+                    GaussianMutation::new(0.01, 0.1) // about the spread of the old uniform 0.2 step
+                ).with_elite_count(1)).collect(),
+                POPULATION_SIZE as usize / ISLAND_COUNT,
+            )
+            .and_then(|model| model.with_migration(Topology::Ring, 5, 1))
+            .expect("one migrant per island fits next to the elite"),
+            evolver: Evolver::new().with_stop_condition(StopCondition::Stagnation(MAX_STAGNANT_GENERATIONS)),
+            stalled: false,
             width,
             height,
             evolution_epoch: 0,
@@ -39,7 +51,7 @@ impl Simulation {
         //     self.evolution_epoch += 1;
         // }
 
-        if self.world.age >= MAX_GENERATION_AGE {
+        if self.world.age >= MAX_GENERATION_AGE && !(STOP_ON_STAGNATION && self.stalled) {
             self.evolve();
             self.evolution_epoch += 1;
         } else {
@@ -47,21 +59,14 @@ impl Simulation {
         }
     }
 
-    fn get_generation_info(&self, population: &Vec<CreatureIndividual> ) {
-        let mut min_eat: f32 = population[0].fitness();
-        let mut max_eat: f32 = population[0].fitness();
-        let mut total_eat: f32 = 0.0;
-        for individual in population {
-            let fitness = individual.fitness();
-            min_eat = min_eat.min(fitness);
-            max_eat = max_eat.max(fitness);
-            total_eat += individual.fitness();
-        }
-        let avg_eat = total_eat / POPULATION_SIZE as f32;
-
-        // println!("min: {}, max: {}, avg: {} - evolution: {}, world age: {}", min_eat, max_eat, avg_eat, self.evolution_epoch, self.world.age);
-        println!("min: {}, max: {}, avg: {} - evolution: {}, foods left num: {}", min_eat, max_eat, avg_eat, self.evolution_epoch, self.world.foods_left_num);
+    fn get_generation_info(&self, population: &[CreatureIndividual]) {
+        let statistics = Statistics::new(population);
 
+        println!(
+            "min: {}, max: {}, avg: {}, median: {}, diversity: {} - evolution: {}, foods left num: {}",
+            statistics.min(), statistics.max(), statistics.mean(), statistics.median(), statistics.mean_distance(),
+            self.evolution_epoch, self.world.foods_left_num,
+        );
     }
 
     fn evolve(&mut self) {
@@ -77,24 +82,36 @@ impl Simulation {
         .collect();
 
         self.get_generation_info(&population);
+        let reason = self.evolver.observe(&population);
+        if let Some(reason) = reason.filter(|_| !self.stalled) {
+            println!(
+                "no new best fitness for {} generations, at generation {}: {:?}",
+                MAX_STAGNANT_GENERATIONS, self.evolver.generation(), reason,
+            );
+        }
+        self.stalled = reason.is_some();
+        if STOP_ON_STAGNATION && self.stalled {
+            return;
+        }
 
-        let best_individual_brain = &self.world.creatures
-        .iter()
-        .max_by(|&a, &b| a.eat.cmp(&b.eat))
-        .expect("Failed to find the best creature")
-        .brain;
+        // creatures keep their order across worlds, so consecutive chunks are stable islands
+        let island_size = population.len() / ISLAND_COUNT;
+        let mut islands: Vec<Vec<CreatureIndividual>> = Vec::with_capacity(ISLAND_COUNT);
+        let mut population = population.into_iter();
+        for _ in 0..ISLAND_COUNT {
+            islands.push(population.by_ref().take(island_size).collect());
+        }
 
-        let children = self.genetic_algorithm.evolve(&mut rng, &population);
+        let children = self.genetic_algorithm.evolve(&mut rng, &islands);
 
-        let mut brains: Vec<Network> = children
+        let brains: Vec<Network> = children
         .iter()
+        .flatten()
         .map(|child| {
             child.into_brain()
         })
         .collect();
 
-        brains.push(best_individual_brain.clone());
-
         self.world = World::new(self.width, self.height, Some(brains), FOOD_NUMBER);
     }
 }
\ No newline at end of file
@@ -0,0 +1,341 @@
+use rand::{Rng, RngCore};
+use rand_distr::StandardNormal;
+
+use crate::*;
+
+// CMA-ES (Hansen's (mu/mu_w, lambda) variant with rank-one and rank-mu covariance updates), maximising fitness.
+// Split in two like the other drivers: `ask` samples a batch, the caller evaluates it, `tell` adapts the distribution.
+// Works in f64 internally; chromosomes stay f32.
+pub struct CmaEs {
+    mean: Vec<f64>,
+    sigma: f64,
+    lambda: usize, // samples per generation
+    weights: Vec<f64>, // recombination weights of the best mu samples
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    chi_n: f64, // expected length of a standard normal vector
+    covariance: Vec<Vec<f64>>,
+    path_sigma: Vec<f64>,
+    path_c: Vec<f64>,
+    basis: Vec<Vec<f64>>, // eigenvectors of the covariance, as columns
+    scales: Vec<f64>,     // square roots of its eigenvalues
+    generation: usize,
+    decomposed_at: usize,
+}
+
+impl CmaEs {
+    // Starts from `mean` with step size `sigma` and the default population size 4 + 3 ln(n)
+    pub fn new(mean: Chromosome, sigma: f32) -> Self {
+        let n = mean.len();
+        assert!(n > 0);
+        Self::with_population_size(mean, sigma, 4 + (3.0 * (n as f64).ln()).floor() as usize)
+    }
+
+    pub fn with_population_size(mean: Chromosome, sigma: f32, lambda: usize) -> Self {
+        assert!(!mean.is_empty());
+        assert!(sigma > 0.0);
+        assert!(lambda >= 2);
+
+        let n = mean.len() as f64;
+        let mu = lambda / 2;
+        let raw: Vec<f64> = (1..=mu).map(|rank| (mu as f64 + 0.5).ln() - (rank as f64).ln()).collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|weight| weight / total).collect();
+        let mu_eff = 1.0 / weights.iter().map(|weight| weight * weight).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff));
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        let size = mean.len();
+        Self {
+            mean: mean.iter().map(|&gene| gene as f64).collect(),
+            sigma: sigma as f64,
+            lambda,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n,
+            covariance: identity(size),
+            path_sigma: vec![0.0; size],
+            path_c: vec![0.0; size],
+            basis: identity(size),
+            scales: vec![1.0; size],
+            generation: 0,
+            decomposed_at: 0,
+        }
+    }
+
+    pub fn mean(&self) -> Chromosome {
+        self.mean.iter().map(|&gene| gene as f32).collect()
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma as f32
+    }
+
+    pub fn population_size(&self) -> usize {
+        self.lambda
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // A batch of `population_size` candidates drawn from N(mean, sigma² C)
+    pub fn ask<I>(&self, rng: &mut dyn RngCore) -> Vec<I>
+    where
+        I: Individual<Gene = f32>
+    {
+        let n = self.mean.len();
+        (0..self.lambda)
+            .map(|_| {
+                let scaled: Vec<f64> = self.scales.iter().map(|scale| scale * rng.sample::<f64, _>(StandardNormal)).collect();
+                (0..n)
+                    .map(|row| {
+                        let step: f64 = self.basis[row].iter().zip(&scaled).map(|(b, z)| b * z).sum();
+                        (self.mean[row] + self.sigma * step) as f32
+                    })
+                    .collect()
+            })
+            .map(I::create)
+            .collect()
+    }
+
+    // Moves the mean towards the best of the evaluated batch and adapts step size and covariance
+    pub fn tell<I>(&mut self, evaluated: &[I])
+    where
+        I: Individual<Gene = f32>
+    {
+        assert!(evaluated.len() >= self.weights.len());
+        let n = self.mean.len();
+
+        let mut ranked: Vec<&I> = evaluated.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        // steps of the selected samples, in units of sigma
+        let steps: Vec<Vec<f64>> = ranked
+            .iter()
+            .take(self.weights.len())
+            .map(|individual| {
+                assert_eq!(individual.chromosome().len(), n);
+                individual
+                    .chromosome()
+                    .iter()
+                    .zip(&self.mean)
+                    .map(|(&gene, mean)| (gene as f64 - mean) / self.sigma)
+                    .collect()
+            })
+            .collect();
+
+        let shift: Vec<f64> = (0..n)
+            .map(|index| self.weights.iter().zip(&steps).map(|(weight, step)| weight * step[index]).sum())
+            .collect();
+        for (mean, delta) in self.mean.iter_mut().zip(&shift) {
+            *mean += self.sigma * delta;
+        }
+
+        // step-size path, in the whitened space C^(-1/2) shift
+        let whitened = self.inverse_sqrt_times(&shift);
+        let rate = (self.c_sigma * (2.0 - self.c_sigma) * self.mu_eff).sqrt();
+        for (path, value) in self.path_sigma.iter_mut().zip(&whitened) {
+            *path = (1.0 - self.c_sigma) * *path + rate * value;
+        }
+        let path_length = norm(&self.path_sigma);
+
+        self.generation += 1;
+        // stalls the rank-one update while the step-size path is unusually long
+        let decay = 1.0 - (1.0 - self.c_sigma).powi(2 * self.generation as i32);
+        let h_sigma = path_length / decay.sqrt() / self.chi_n < 1.4 + 2.0 / (n as f64 + 1.0);
+
+        let rate = (self.c_c * (2.0 - self.c_c) * self.mu_eff).sqrt();
+        for (path, value) in self.path_c.iter_mut().zip(&shift) {
+            *path = (1.0 - self.c_c) * *path + if h_sigma { rate * value } else { 0.0 };
+        }
+
+        let correction = if h_sigma { 0.0 } else { self.c_c * (2.0 - self.c_c) };
+        let keep = 1.0 - self.c_1 - self.c_mu;
+        for row in 0..n {
+            for column in 0..=row {
+                let rank_one = self.path_c[row] * self.path_c[column] + correction * self.covariance[row][column];
+                let rank_mu: f64 = self
+                    .weights
+                    .iter()
+                    .zip(&steps)
+                    .map(|(weight, step)| weight * step[row] * step[column])
+                    .sum();
+                let value = keep * self.covariance[row][column] + self.c_1 * rank_one + self.c_mu * rank_mu;
+                self.covariance[row][column] = value;
+                self.covariance[column][row] = value;
+            }
+        }
+
+        self.sigma *= ((self.c_sigma / self.d_sigma) * (path_length / self.chi_n - 1.0)).exp();
+
+        // the decomposition is O(n³), so it is only refreshed every few generations on large problems
+        let interval = (self.lambda as f64 / (self.c_1 + self.c_mu) / n as f64 / 10.0).max(1.0) as usize;
+        if self.generation - self.decomposed_at >= interval {
+            self.decompose();
+        }
+    }
+
+    fn decompose(&mut self) {
+        let (values, vectors) = symmetric_eigen(&self.covariance);
+        self.scales = values.iter().map(|value| value.max(1e-20).sqrt()).collect();
+        self.basis = vectors;
+        self.decomposed_at = self.generation;
+    }
+
+    // B D^-1 B^T v
+    fn inverse_sqrt_times(&self, vector: &[f64]) -> Vec<f64> {
+        let n = vector.len();
+        let projected: Vec<f64> = (0..n)
+            .map(|column| {
+                let dot: f64 = (0..n).map(|row| self.basis[row][column] * vector[row]).sum();
+                dot / self.scales[column]
+            })
+            .collect();
+        self.basis
+            .iter()
+            .map(|row| row.iter().zip(&projected).map(|(b, p)| b * p).sum())
+            .collect()
+    }
+}
+
+fn identity(size: usize) -> Vec<Vec<f64>> {
+    (0..size)
+        .map(|row| (0..size).map(|column| if row == column { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+// Cyclic Jacobi rotations; returns the eigenvalues and the eigenvectors as columns
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = identity(n);
+
+    for _ in 0..64 {
+        let off_diagonal: f64 = (0..n).flat_map(|p| (p + 1..n).map(move |q| (p, q))).map(|(p, q)| a[p][q] * a[p][q]).sum();
+        let diagonal: f64 = (0..n).map(|p| a[p][p] * a[p][p]).sum();
+        if off_diagonal <= 1e-24 * diagonal.max(f64::MIN_POSITIVE) {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (pk, qk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    (*pk, *qk) = (c * *pk - s * *qk, s * *pk + c * *qk);
+                }
+                for row in vectors.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|p| a[p][p]).collect(), vectors)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // maximise -sum(i * x_i²): ill-conditioned, where covariance adaptation pays off
+    struct Ellipsoid {
+        chromosome: Chromosome,
+        fitness: f32,
+    }
+
+    impl Individual for Ellipsoid {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            let fitness = -chromosome
+                .iter()
+                .enumerate()
+                .map(|(index, gene)| 10f32.powi(index as i32) * gene * gene)
+                .sum::<f32>();
+            Self { chromosome, fitness }
+        }
+    }
+
+    #[test]
+    fn eigen_decomposition() {
+        let matrix = vec![vec![4.0, 1.0, 0.5], vec![1.0, 3.0, 0.2], vec![0.5, 0.2, 1.0]];
+        let (values, vectors) = symmetric_eigen(&matrix);
+
+        // B diag(values) B^T rebuilds the matrix
+        for row in 0..3 {
+            for column in 0..3 {
+                let rebuilt: f64 = (0..3).map(|k| vectors[row][k] * values[k] * vectors[column][k]).sum();
+                assert_relative_eq!(rebuilt, matrix[row][column], epsilon = 1e-9);
+            }
+        }
+        let trace: f64 = values.iter().sum();
+        assert_relative_eq!(trace, 8.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn default_population_size() {
+        let cma = CmaEs::new(vec![0.0; 10].into_iter().collect(), 0.5);
+        assert_eq!(cma.population_size(), 10);
+    }
+
+    #[test]
+    fn solves_ill_conditioned_problem() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut cma = CmaEs::new(vec![1.0; 5].into_iter().collect(), 0.5);
+
+        for _ in 0..300 {
+            let batch: Vec<Ellipsoid> = cma.ask(&mut rng);
+            cma.tell(&batch);
+        }
+
+        let best = Ellipsoid::create(cma.mean());
+        assert!(best.fitness() > -1e-6, "{}", best.fitness());
+        assert!(cma.sigma() < 0.01);
+        assert_eq!(cma.generation(), 300);
+    }
+}
@@ -0,0 +1,286 @@
+use rand::seq::index;
+use rand::{Rng, RngCore};
+
+use crate::*;
+
+// Cuts both parents at `points` random places and alternates between them segment by segment
+pub struct NPointCrossover {
+    points: usize,
+}
+
+impl NPointCrossover {
+    pub fn new(points: usize) -> Self {
+        assert!(points > 0);
+        Self { points }
+    }
+}
+
+impl<G: Clone> CrossoverMethod<G> for NPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+        if parent_a.len() < 2 {
+            return parent_a.clone();
+        }
+
+        // cut positions 1..len, so every segment is non-empty
+        let points = self.points.min(parent_a.len() - 1);
+        let mut cuts: Vec<usize> = index::sample(rng, parent_a.len() - 1, points)
+            .into_iter()
+            .map(|cut| cut + 1)
+            .collect();
+        cuts.sort_unstable();
+
+        let mut from_b = false;
+        let mut cuts = cuts.into_iter().peekable();
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .enumerate()
+            .map(|(position, (a, b))| {
+                if cuts.next_if_eq(&position).is_some() {
+                    from_b = !from_b;
+                }
+                if from_b { b.clone() } else { a.clone() }
+            })
+            .collect()
+    }
+}
+
+pub struct SinglePointCrossover;
+
+impl<G: Clone> CrossoverMethod<G> for SinglePointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        NPointCrossover::new(1).crossover(rng, parent_a, parent_b)
+    }
+}
+
+// child = w * a + (1 - w) * b, with one random w per child
+pub struct ArithmeticCrossover;
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+        let weight: f32 = rng.gen_range(0.0..=1.0);
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| weight * a + (1.0 - weight) * b)
+            .collect()
+    }
+}
+
+// Each gene drawn uniformly from the parents' interval widened by `alpha` of its length on both sides
+pub struct BlxAlphaCrossover {
+    alpha: f32, // 0.0 - 1.0, 0.5 is the usual choice
+}
+
+impl BlxAlphaCrossover {
+    pub fn new(alpha: f32) -> Self {
+        assert!(alpha >= 0.0);
+        Self { alpha }
+    }
+}
+
+impl CrossoverMethod for BlxAlphaCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let (low, high) = (a.min(b), a.max(b));
+                let spread = self.alpha * (high - low);
+                rng.gen_range(low - spread..=high + spread)
+            })
+            .collect()
+    }
+}
+
+// Simulated binary crossover (Deb & Agrawal); larger `eta` keeps children closer to their parents
+pub struct SimulatedBinaryCrossover {
+    eta: f32, // distribution index, typically 2.0 - 20.0
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(eta: f32) -> Self {
+        assert!(eta >= 0.0);
+        Self { eta }
+    }
+}
+
+impl CrossoverMethod for SimulatedBinaryCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+        let exponent = 1.0 / (self.eta + 1.0);
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let u: f32 = rng.gen_range(0.0..1.0);
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(exponent)
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(exponent)
+                };
+                // SBX makes two mirrored children; keep one of them at random
+                let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                0.5 * ((a + b) + sign * beta * (a - b))
+            })
+            .collect()
+    }
+}
+
+// Uniform crossover over whole blocks, e.g. one block per neuron so its bias and weights stay together.
+// `blocks` are the block lengths in chromosome order; neural-network's `Network::neuron_blocks` computes them.
+pub struct NeuronAlignedCrossover {
+    blocks: Vec<usize>,
+}
+
+impl NeuronAlignedCrossover {
+    pub fn new(blocks: Vec<usize>) -> Self {
+        assert!(blocks.iter().all(|&block| block > 0));
+        Self { blocks }
+    }
+}
+
+impl<G: Clone> CrossoverMethod<G> for NeuronAlignedCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+        assert_eq!(parent_a.len(), self.blocks.iter().sum::<usize>(), "blocks should cover the chromosome");
+
+        let mut genes = Vec::with_capacity(parent_a.len());
+        let mut start = 0;
+        for &block in &self.blocks {
+            let parent = if rng.gen_bool(0.5) { parent_a } else { parent_b };
+            genes.extend(parent.as_slice()[start..start + block].iter().cloned());
+            start += block;
+        }
+        genes.into_iter().collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parents() -> (Chromosome, Chromosome) {
+        let parent_a: Chromosome = (1..=100).map(|n| n as f32).collect();
+        let parent_b: Chromosome = (1..=100).map(|n| -n as f32).collect();
+        (parent_a, parent_b)
+    }
+
+    // number of places where the child switches from one parent to the other
+    fn switches(child: &Chromosome) -> usize {
+        let signs: Vec<bool> = child.iter().map(|&gene| gene > 0.0).collect();
+        signs.windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+
+    #[test]
+    fn single_point_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        let child = SinglePointCrossover.crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(switches(&child), 1);
+        assert_eq!(child[0], 1.0);
+    }
+
+    #[test]
+    fn n_point_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        let child = NPointCrossover::new(4).crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(switches(&child), 4);
+        assert!(child.iter().enumerate().all(|(index, gene)| gene.abs() == (index + 1) as f32));
+    }
+
+    #[test]
+    fn arithmetic_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        let child = ArithmeticCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        // the same blend everywhere, so every gene is the same fraction of its parent
+        let ratio = child[0] / parent_a[0];
+        assert!((-1.0..=1.0).contains(&ratio));
+        assert!(child.iter().zip(parent_a.iter()).all(|(c, a)| (c / a - ratio).abs() < 1e-4));
+    }
+
+    #[test]
+    fn blx_alpha_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        let child = BlxAlphaCrossover::new(0.5).crossover(&mut rng, &parent_a, &parent_b);
+
+        for (index, gene) in child.iter().enumerate() {
+            let bound = 2.0 * (index + 1) as f32;
+            assert!(gene.abs() <= bound);
+        }
+        assert!(child.iter().enumerate().any(|(index, gene)| gene.abs() > (index + 1) as f32));
+    }
+
+    #[test]
+    fn simulated_binary_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = vec![1.0; 1000].into_iter().collect();
+        let parent_b: Chromosome = vec![3.0; 1000].into_iter().collect();
+        let child = SimulatedBinaryCrossover::new(20.0).crossover(&mut rng, &parent_a, &parent_b);
+
+        // a high eta keeps children close to a parent and centred on the parents' mean
+        let mean = child.iter().sum::<f32>() / child.len() as f32;
+        assert!((mean - 2.0).abs() < 0.1);
+        let near_parent = child.iter().filter(|&&gene| (gene - 1.0).abs() < 0.3 || (gene - 3.0).abs() < 0.3).count();
+        assert!(near_parent > 900);
+    }
+
+    #[test]
+    fn neuron_aligned_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = (1..=12).map(|n| n as f32).collect();
+        let parent_b: Chromosome = (1..=12).map(|n| -n as f32).collect();
+        let blocks = vec![4, 4, 2, 2];
+        let child = NeuronAlignedCrossover::new(blocks.clone()).crossover(&mut rng, &parent_a, &parent_b);
+
+        let mut start = 0;
+        for block in blocks {
+            let signs: Vec<bool> = (start..start + block).map(|index| child[index] > 0.0).collect();
+            assert!(signs.iter().all(|&sign| sign == signs[0]), "block starting at {} was split", start);
+            start += block;
+        }
+    }
+}
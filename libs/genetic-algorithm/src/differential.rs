@@ -0,0 +1,156 @@
+use rand::seq::index;
+use rand::{Rng, RngCore};
+
+use crate::*;
+
+// Which vector the scaled difference is added to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeStrategy {
+    Rand1Bin, // a random member: explores
+    Best1Bin, // the current best: converges faster, easier to trap
+}
+
+// Differential evolution. Like `Nsga2` it is split in two so the caller can evaluate in between:
+// `trials` builds one trial vector per member, `select` keeps whichever of each pair is fitter.
+pub struct DifferentialEvolution {
+    strategy: DeStrategy,
+    weight: f32,         // F, scales the difference vector, usually 0.4 - 1.0
+    crossover_rate: f32, // CR, 0.0 - 1.0, chance of taking each gene from the mutant
+}
+
+impl DifferentialEvolution {
+    pub fn new(strategy: DeStrategy, weight: f32, crossover_rate: f32) -> Self {
+        assert!(weight > 0.0);
+        assert!((0.0..=1.0).contains(&crossover_rate));
+        Self { strategy, weight, crossover_rate }
+    }
+
+    // One trial per member, in population order
+    pub fn trials<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    where
+        I: Individual<Gene = f32>
+    {
+        assert!(population.len() >= 4, "differential evolution needs at least four members");
+        let best = population
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.fitness().total_cmp(&b.fitness()))
+            .map(|(index, _)| index)
+            .unwrap();
+
+        population
+            .iter()
+            .enumerate()
+            .map(|(target, individual)| {
+                // three distinct members other than the target
+                let others: Vec<usize> = index::sample(rng, population.len() - 1, 3)
+                    .into_iter()
+                    .map(|other| if other >= target { other + 1 } else { other })
+                    .collect();
+                let base = match self.strategy {
+                    DeStrategy::Rand1Bin => others[0],
+                    DeStrategy::Best1Bin => best,
+                };
+                let (base, a, b) = (
+                    population[base].chromosome(),
+                    population[others[1]].chromosome(),
+                    population[others[2]].chromosome(),
+                );
+
+                let genes = individual.chromosome();
+                let forced = rng.gen_range(0..genes.len().max(1)); // at least one gene comes from the mutant
+                let trial = genes
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &gene)| {
+                        if position == forced || rng.gen_bool(self.crossover_rate as f64) {
+                            base[position] + self.weight * (a[position] - b[position])
+                        } else {
+                            gene
+                        }
+                    })
+                    .collect();
+                I::create(trial)
+            })
+            .collect()
+    }
+
+    // Pairs up members and their evaluated trials; a trial at least as fit replaces its member
+    pub fn select<I>(&self, population: Vec<I>, trials: Vec<I>) -> Vec<I>
+    where
+        I: Individual<Gene = f32>
+    {
+        assert_eq!(population.len(), trials.len());
+        population
+            .into_iter()
+            .zip(trials)
+            .map(|(member, trial)| if trial.fitness() >= member.fitness() { trial } else { member })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // maximise -sum(x²), evaluated on creation like a creature after its run
+    struct Sphere {
+        chromosome: Chromosome,
+        fitness: f32,
+    }
+
+    impl Individual for Sphere {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            let fitness = -chromosome.iter().map(|gene| gene * gene).sum::<f32>();
+            Self { chromosome, fitness }
+        }
+    }
+
+    fn optimise(strategy: DeStrategy) -> f32 {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let de = DifferentialEvolution::new(strategy, 0.6, 0.9);
+        let mut population: Vec<Sphere> = (0..20)
+            .map(|_| Sphere::create((0..5).map(|_| rng.gen_range(-5.0..5.0)).collect()))
+            .collect();
+
+        for _ in 0..200 {
+            let trials = de.trials(&mut rng, &population);
+            population = de.select(population, trials);
+        }
+        population.iter().map(Individual::fitness).fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    #[test]
+    fn rand_1_bin() {
+        let best = optimise(DeStrategy::Rand1Bin);
+        assert!(best > -1e-3, "{}", best);
+    }
+
+    #[test]
+    fn best_1_bin() {
+        let best = optimise(DeStrategy::Best1Bin);
+        assert!(best > -1e-6, "{}", best);
+    }
+
+    #[test]
+    fn selection_keeps_the_fitter() {
+        let member = |x: f32| Sphere::create(vec![x].into_iter().collect());
+        let de = DifferentialEvolution::new(DeStrategy::Rand1Bin, 0.5, 0.5);
+        let kept = de.select(vec![member(1.0), member(3.0)], vec![member(2.0), member(-0.5)]);
+        let kept: Vec<f32> = kept.iter().map(|individual| individual.chromosome()[0]).collect();
+        assert_eq!(kept, vec![1.0, -0.5]);
+    }
+}
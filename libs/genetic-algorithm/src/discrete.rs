@@ -0,0 +1,238 @@
+use std::ops::RangeInclusive;
+
+use rand::{Rng, RngCore};
+
+use crate::*;
+
+// Flips each bit with probability `chance`
+pub struct BitFlipMutation {
+    chance: f32, // 0.0 - 1.0
+}
+
+impl BitFlipMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl MutationMethod<bool> for BitFlipMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<bool>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = !*gene;
+            }
+        }
+    }
+}
+
+// Redraws each gene uniformly from `range` with probability `chance`
+pub struct RandomResetMutation {
+    chance: f32, // 0.0 - 1.0
+    range: RangeInclusive<i32>,
+}
+
+impl RandomResetMutation {
+    pub fn new(chance: f32, range: RangeInclusive<i32>) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(!range.is_empty());
+        Self { chance, range }
+    }
+}
+
+impl MutationMethod<i32> for RandomResetMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<i32>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = rng.gen_range(self.range.clone());
+            }
+        }
+    }
+}
+
+// With probability `chance`, swaps two random positions; keeps permutations valid
+pub struct SwapMutation {
+    chance: f32, // 0.0 - 1.0
+}
+
+impl SwapMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for SwapMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if child.len() < 2 || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+        let a = rng.gen_range(0..child.len());
+        let b = rng.gen_range(0..child.len());
+        child.as_mut_slice().swap(a, b);
+    }
+}
+
+// With probability `chance`, reverses a random segment; keeps permutations valid
+pub struct InversionMutation {
+    chance: f32, // 0.0 - 1.0
+}
+
+impl InversionMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for InversionMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if child.len() < 2 || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+        let a = rng.gen_range(0..child.len());
+        let b = rng.gen_range(0..child.len());
+        child.as_mut_slice()[a.min(b)..=a.max(b)].reverse();
+    }
+}
+
+// OX1: copies a random slice of parent A in place, then fills the gaps with the missing genes in parent B's order.
+// Both parents must be permutations of the same genes.
+pub struct OrderCrossover;
+
+impl<G: Clone + PartialEq> CrossoverMethod<G> for OrderCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+        let len = parent_a.len();
+        if len < 2 {
+            return parent_a.clone();
+        }
+
+        let (a, b) = (rng.gen_range(0..len), rng.gen_range(0..len));
+        let (start, end) = (a.min(b), a.max(b));
+        let kept = &parent_a.as_slice()[start..=end];
+
+        // parent B's genes, starting after the kept slice, minus those already kept
+        let mut fill = (0..len)
+            .map(|offset| &parent_b[(end + 1 + offset) % len])
+            .filter(|gene| !kept.contains(gene));
+
+        let mut genes: Vec<Option<G>> = vec![None; len];
+        for (position, gene) in (start..=end).zip(kept) {
+            genes[position] = Some(gene.clone());
+        }
+        for offset in 0..len - kept.len() {
+            let position = (end + 1 + offset) % len;
+            genes[position] = fill.next().cloned();
+        }
+
+        genes
+            .into_iter()
+            .map(|gene| gene.expect("parents should be permutations of the same genes"))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn is_permutation(chromosome: &Chromosome<usize>, len: usize) -> bool {
+        let mut genes = chromosome.as_slice().to_vec();
+        genes.sort_unstable();
+        genes == (0..len).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn bit_flip_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<bool> = vec![false; 1000].into_iter().collect();
+        BitFlipMutation::new(0.25).mutate(&mut rng, &mut child);
+        let flipped = child.iter().filter(|&&bit| bit).count();
+        assert!((200..300).contains(&flipped));
+
+        BitFlipMutation::new(1.0).mutate(&mut rng, &mut child);
+        assert_eq!(child.iter().filter(|&&bit| bit).count(), 1000 - flipped);
+    }
+
+    #[test]
+    fn random_reset_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<i32> = vec![100; 100].into_iter().collect();
+        RandomResetMutation::new(1.0, -3..=3).mutate(&mut rng, &mut child);
+        assert!(child.iter().all(|gene| (-3..=3).contains(gene)));
+    }
+
+    #[test]
+    fn swap_and_inversion_keep_permutations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<usize> = (0..20).collect();
+
+        for _ in 0..50 {
+            SwapMutation::new(1.0).mutate(&mut rng, &mut child);
+            InversionMutation::new(1.0).mutate(&mut rng, &mut child);
+            assert!(is_permutation(&child, 20));
+        }
+        assert_ne!(child.as_slice(), (0..20).collect::<Vec<_>>().as_slice());
+    }
+
+    #[test]
+    fn order_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<usize> = (0..10).collect();
+        let parent_b: Chromosome<usize> = (0..10).rev().collect();
+
+        for _ in 0..20 {
+            let child = OrderCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            assert!(is_permutation(&child, 10));
+        }
+    }
+
+    #[test]
+    fn generic_algorithm_over_bits() {
+        struct Bits(Chromosome<bool>);
+
+        impl Individual for Bits {
+            type Gene = bool;
+
+            fn fitness(&self) -> f32 {
+                self.0.iter().filter(|&&bit| bit).count() as f32
+            }
+
+            fn chromosome(&self) -> &Chromosome<bool> {
+                &self.0
+            }
+
+            fn create(chromosome: Chromosome<bool>) -> Self {
+                Self(chromosome)
+            }
+        }
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut ga = GenericAlgorithm::new(
+            TournamentSelection::new(2, 1.0),
+            UniformCrossover,
+            BitFlipMutation::new(0.02),
+        )
+        .with_elite_count(1);
+
+        let mut population: Vec<Bits> = (0..20)
+            .map(|_| Bits((0..32).map(|_| rng.gen_bool(0.2)).collect()))
+            .collect();
+        let start = population.iter().map(Individual::fitness).fold(0.0, f32::max);
+
+        for _ in 0..40 {
+            population = ga.evolve(&mut rng, &population);
+        }
+        let end = population.iter().map(Individual::fitness).fold(0.0, f32::max);
+        assert!(end > start + 10.0, "{} -> {}", start, end);
+    }
+}
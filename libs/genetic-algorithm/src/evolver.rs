@@ -0,0 +1,279 @@
+use std::time::{Duration, Instant};
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopCondition {
+    MaxGenerations(usize),
+    TargetFitness(f32), // the best individual reached it
+    Stagnation(usize),  // the best fitness so far has not gone up for this many generations
+    TimeLimit(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    MaxGenerations,
+    TargetFitness,
+    Stagnation,
+    TimeLimit,
+    Observer, // an observer returned `Control::Stop`
+}
+
+// What an observer wants to happen after seeing a generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+// Summary of one evaluated generation, handed to every observer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationReport {
+    pub generation: usize, // 0 for the initial population
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+    pub worst_fitness: f32,
+    pub best_so_far: f32,
+    pub stagnant_for: usize,
+    pub elapsed: Duration, // since the first generation was observed
+}
+
+// How a `run` ended
+pub struct Outcome<I> {
+    pub population: Vec<I>, // the last evaluated generation
+    pub generations: usize,
+    pub reason: StopReason,
+}
+
+type Observer<'a, I> = Box<dyn FnMut(&GenerationReport, &[I]) -> Control + 'a>;
+
+// Run-loop control around any single-step optimiser: stop conditions plus per-generation observers for logging,
+// checkpointing and early stopping. Frame-driven callers can use `observe` on its own instead of `run`.
+pub struct Evolver<'a, I> {
+    conditions: Vec<StopCondition>,
+    observers: Vec<Observer<'a, I>>,
+    generation: usize,
+    best_so_far: Option<f32>,
+    stagnant_for: usize,
+    started: Option<Instant>,
+}
+
+impl<'a, I> Default for Evolver<'a, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, I> Evolver<'a, I> {
+    pub fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+            observers: Vec::new(),
+            generation: 0,
+            best_so_far: None,
+            stagnant_for: 0,
+            started: None,
+        }
+    }
+
+    pub fn with_stop_condition(mut self, condition: StopCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn with_observer(mut self, observer: impl FnMut(&GenerationReport, &[I]) -> Control + 'a) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+impl<'a, I> Evolver<'a, I>
+where
+    I: Individual,
+{
+    // Records an evaluated generation, tells the observers and checks the stop conditions
+    pub fn observe(&mut self, population: &[I]) -> Option<StopReason> {
+        assert!(!population.is_empty());
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        let fitness: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        let best = fitness.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let worst = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+        let mean = fitness.iter().sum::<f32>() / fitness.len() as f32;
+
+        match self.best_so_far {
+            Some(previous) if best <= previous => self.stagnant_for += 1,
+            _ => {
+                self.best_so_far = Some(best);
+                self.stagnant_for = 0;
+            }
+        }
+
+        let report = GenerationReport {
+            generation: self.generation,
+            best_fitness: best,
+            mean_fitness: mean,
+            worst_fitness: worst,
+            best_so_far: self.best_so_far.unwrap(),
+            stagnant_for: self.stagnant_for,
+            elapsed: started.elapsed(),
+        };
+        self.generation += 1;
+
+        // every observer sees every generation, even when an earlier one asked to stop
+        let mut stop = false;
+        for observer in &mut self.observers {
+            stop |= observer(&report, population) == Control::Stop;
+        }
+        if stop {
+            return Some(StopReason::Observer);
+        }
+
+        self.conditions.iter().find_map(|condition| match *condition {
+            StopCondition::MaxGenerations(limit) if self.generation >= limit => Some(StopReason::MaxGenerations),
+            StopCondition::TargetFitness(target) if best >= target => Some(StopReason::TargetFitness),
+            StopCondition::Stagnation(limit) if report.stagnant_for >= limit => Some(StopReason::Stagnation),
+            StopCondition::TimeLimit(budget) if report.elapsed >= budget => Some(StopReason::TimeLimit),
+            _ => None,
+        })
+    }
+
+    // Observes `population`, then keeps replacing it with `step(&population)` until something says stop.
+    // `step` breeds and evaluates the next generation, e.g. `GenericAlgorithm::evolve` followed by a fitness run.
+    pub fn run(&mut self, mut population: Vec<I>, mut step: impl FnMut(&[I]) -> Vec<I>) -> Outcome<I> {
+        assert!(
+            !self.conditions.is_empty() || !self.observers.is_empty(),
+            "without stop conditions or observers the run would never end"
+        );
+        let first = self.generation;
+
+        loop {
+            if let Some(reason) = self.observe(&population) {
+                return Outcome { population, generations: self.generation - first, reason };
+            }
+            population = step(&population);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // fitness is the single gene
+    struct Value(Chromosome);
+
+    impl Individual for Value {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.0[0]
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    fn values(values: &[f32]) -> Vec<Value> {
+        values.iter().map(|&value| Value(vec![value].into_iter().collect())).collect()
+    }
+
+    // every generation gets `by` fitter, until it reaches `ceiling`
+    fn climb(by: f32, ceiling: f32) -> impl FnMut(&[Value]) -> Vec<Value> {
+        move |population: &[Value]| {
+            let next: Vec<f32> = population.iter().map(|value| (value.fitness() + by).min(ceiling)).collect();
+            values(&next)
+        }
+    }
+
+    #[test]
+    fn reports() {
+        let mut reports = Vec::new();
+        let mut evolver = Evolver::new()
+            .with_stop_condition(StopCondition::MaxGenerations(3))
+            .with_observer(|report, _| {
+                reports.push(*report);
+                Control::Continue
+            });
+
+        let outcome = evolver.run(values(&[1.0, 2.0, 6.0]), climb(1.0, 7.0));
+        assert_eq!(outcome.reason, StopReason::MaxGenerations);
+        assert_eq!(outcome.generations, 3);
+        drop(evolver);
+
+        let best: Vec<f32> = reports.iter().map(|report| report.best_fitness).collect();
+        assert_eq!(best, vec![6.0, 7.0, 7.0]);
+        assert_relative_eq!(reports[0].mean_fitness, 3.0);
+        assert_relative_eq!(reports[2].worst_fitness, 3.0);
+        assert_eq!(reports[2].stagnant_for, 1);
+    }
+
+    #[test]
+    fn target_fitness() {
+        let mut evolver = Evolver::new().with_stop_condition(StopCondition::TargetFitness(5.0));
+        let outcome = evolver.run(values(&[0.0]), climb(1.0, 100.0));
+        assert_eq!(outcome.reason, StopReason::TargetFitness);
+        assert_eq!(outcome.generations, 6);
+        assert_eq!(outcome.population[0].fitness(), 5.0);
+    }
+
+    #[test]
+    fn stagnation() {
+        let mut evolver = Evolver::new()
+            .with_stop_condition(StopCondition::Stagnation(4))
+            .with_stop_condition(StopCondition::MaxGenerations(100));
+        let outcome = evolver.run(values(&[0.0]), climb(1.0, 2.0));
+        assert_eq!(outcome.reason, StopReason::Stagnation);
+        assert_eq!(outcome.generations, 7);
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut evolver = Evolver::new().with_stop_condition(StopCondition::TimeLimit(Duration::from_millis(20)));
+        let outcome = evolver.run(values(&[0.0]), |population: &[Value]| {
+            std::thread::sleep(Duration::from_millis(5));
+            values(&[population[0].fitness() + 1.0])
+        });
+        assert_eq!(outcome.reason, StopReason::TimeLimit);
+        assert!(outcome.generations >= 4);
+    }
+
+    #[test]
+    fn observer_stops_early() {
+        let mut evolver = Evolver::new()
+            .with_stop_condition(StopCondition::MaxGenerations(100))
+            .with_observer(|report, population: &[Value]| {
+                assert_eq!(population.len(), 2);
+                if report.generation == 2 { Control::Stop } else { Control::Continue }
+            });
+        let outcome = evolver.run(values(&[0.0, 1.0]), climb(1.0, 100.0));
+        assert_eq!(outcome.reason, StopReason::Observer);
+        assert_eq!(outcome.generations, 3);
+    }
+
+    #[test]
+    fn drives_generic_algorithm() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut ga = GenericAlgorithm::new(TournamentSelection::new(2, 1.0), BlxAlphaCrossover::new(0.5), GaussianMutation::new(1.0, 0.5))
+            .with_elite_count(1);
+        let mut evolver = Evolver::new()
+            .with_stop_condition(StopCondition::TargetFitness(10.0))
+            .with_stop_condition(StopCondition::MaxGenerations(1000));
+
+        let outcome = evolver.run(values(&[0.0; 10]), |population| ga.evolve(&mut rng, population));
+        assert_eq!(outcome.reason, StopReason::TargetFitness);
+    }
+}
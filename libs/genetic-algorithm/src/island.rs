@@ -0,0 +1,369 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use crate::*;
+
+// Which islands each island sends its migrants to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    Ring,           // island i sends to island i + 1, the last one back to the first
+    FullyConnected, // every island sends to every other island
+    Random,         // every island sends to one other island, redrawn at each migration
+}
+
+// Which individuals leave an island; migrants are copies, the source island keeps its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrantPolicy {
+    Best,
+    Random,
+}
+
+// Why a migration setting was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    ZeroInterval,
+    TooManyMigrants { island: usize, arrivals: usize, free: usize }, // more could arrive than there are non-elite slots
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroInterval => write!(f, "migration interval should be at least 1"),
+            Self::TooManyMigrants { island, arrivals, free } => write!(
+                f,
+                "island {} could receive {} migrants but only has {} slots besides its elites",
+                island, arrivals, free
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+// Several populations, each evolved by its own `GenericAlgorithm`, exchanging a few individuals every `interval` generations.
+// Migrants replace the last offspring of the receiving island, never its elites.
+pub struct IslandModel<S, G = f32> {
+    islands: Vec<GenericAlgorithm<S, G>>,
+    population_size: usize, // per island
+    topology: Topology,
+    interval: usize,
+    migrant_count: usize, // sent along each route
+    policy: MigrantPolicy,
+    generation: usize,
+}
+
+impl<S, G> IslandModel<S, G>
+where
+    S: SelectionMethod,
+    G: Clone,
+{
+    // Ring migration of the best individual every 5 generations until configured otherwise
+    pub fn new(islands: Vec<GenericAlgorithm<S, G>>, population_size: usize) -> Result<Self, MigrationError> {
+        assert!(!islands.is_empty());
+        let model = Self {
+            islands,
+            population_size,
+            topology: Topology::Ring,
+            interval: 5,
+            migrant_count: 1,
+            policy: MigrantPolicy::Best,
+            generation: 0,
+        };
+        model.check_migration()?;
+        Ok(model)
+    }
+
+    pub fn with_migration(mut self, topology: Topology, interval: usize, migrant_count: usize) -> Result<Self, MigrationError> {
+        self.topology = topology;
+        self.interval = interval;
+        self.migrant_count = migrant_count;
+        self.check_migration()?;
+        Ok(self)
+    }
+
+    pub fn with_migrant_policy(mut self, policy: MigrantPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn island_count(&self) -> usize {
+        self.islands.len()
+    }
+
+    // One generation on every island, then migration if it is due; `populations[i]` belongs to island i
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, populations: &[Vec<I>]) -> Vec<Vec<I>>
+    where
+        I: Individual<Gene = G>
+    {
+        assert_eq!(populations.len(), self.islands.len());
+        self.generation += 1;
+
+        // picked from the evaluated populations, since the next generation has no fitness yet
+        let migrants: Vec<Vec<Chromosome<G>>> = if self.generation.is_multiple_of(self.interval) {
+            populations
+                .iter()
+                .map(|population| self.migrants(rng, population))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut next: Vec<Vec<I>> = self
+            .islands
+            .iter_mut()
+            .zip(populations)
+            .map(|(island, population)| island.evolve(rng, population))
+            .collect();
+
+        if migrants.is_empty() {
+            return next;
+        }
+
+        let routes = self.routes(rng);
+        let mut incoming: Vec<Vec<Chromosome<G>>> = vec![Vec::new(); next.len()];
+        for (source, chromosomes) in migrants.iter().enumerate() {
+            for &destination in &routes[source] {
+                incoming[destination].extend(chromosomes.iter().cloned());
+            }
+        }
+
+        for ((population, island), mut arrivals) in next.iter_mut().zip(&self.islands).zip(incoming) {
+            // only reachable with populations other than `population_size`; keep a random share of the arrivals
+            let free = population.len().saturating_sub(island.elite_count());
+            if arrivals.len() > free {
+                arrivals.shuffle(rng);
+                arrivals.truncate(free);
+            }
+            let start = population.len() - arrivals.len();
+            for (slot, chromosome) in population[start..].iter_mut().zip(arrivals) {
+                *slot = I::create(chromosome);
+            }
+        }
+        next
+    }
+
+    fn migrants<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<Chromosome<G>>
+    where
+        I: Individual<Gene = G>
+    {
+        let count = self.migrant_count.min(population.len());
+        let chosen: Vec<&I> = match self.policy {
+            MigrantPolicy::Best => {
+                let mut ranked: Vec<&I> = population.iter().collect();
+                ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+                ranked.truncate(count);
+                ranked
+            }
+            MigrantPolicy::Random => population.choose_multiple(rng, count).collect(),
+        };
+        chosen.into_iter().map(|individual| individual.chromosome().clone()).collect()
+    }
+
+    // Migrants an island can receive at once under the current topology: every other island may pick it under Random
+    fn max_arrivals(&self) -> usize {
+        let sources = match self.topology {
+            _ if self.islands.len() < 2 => 0,
+            Topology::Ring => 1,
+            Topology::FullyConnected | Topology::Random => self.islands.len() - 1,
+        };
+        sources * self.migrant_count.min(self.population_size)
+    }
+
+    fn check_migration(&self) -> Result<(), MigrationError> {
+        if self.interval == 0 {
+            return Err(MigrationError::ZeroInterval);
+        }
+        let arrivals = self.max_arrivals();
+        for (index, island) in self.islands.iter().enumerate() {
+            let free = self.population_size.saturating_sub(island.elite_count());
+            if arrivals > free {
+                return Err(MigrationError::TooManyMigrants { island: index, arrivals, free });
+            }
+        }
+        Ok(())
+    }
+
+    // Destinations per source island
+    fn routes(&self, rng: &mut dyn RngCore) -> Vec<Vec<usize>> {
+        let count = self.islands.len();
+        if count < 2 {
+            return vec![Vec::new(); count];
+        }
+
+        (0..count)
+            .map(|source| match self.topology {
+                Topology::Ring => vec![(source + 1) % count],
+                Topology::FullyConnected => (0..count).filter(|&destination| destination != source).collect(),
+                Topology::Random => {
+                    // any island but the source
+                    let destination = rng.gen_range(0..count - 1);
+                    vec![if destination >= source { destination + 1 } else { destination }]
+                }
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // fitness is the single gene, so migrants are easy to recognise
+    struct Marker(Chromosome);
+
+    impl Individual for Marker {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.0[0]
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    fn marker(value: f32) -> Marker {
+        Marker(vec![value].into_iter().collect())
+    }
+
+    // islands that only copy their parents, so every value stays traceable to its island
+    fn model(count: usize) -> IslandModel<TournamentSelection> {
+        let islands = (0..count)
+            .map(|_| {
+                GenericAlgorithm::new(TournamentSelection::new(2, 1.0), UniformCrossover, GaussianMutation::new(0.0, 0.0))
+                    .with_elite_count(1)
+            })
+            .collect();
+        IslandModel::new(islands, 5).unwrap()
+    }
+
+    // island i holds values i * 10 + 0..5
+    fn populations(count: usize) -> Vec<Vec<Marker>> {
+        (0..count)
+            .map(|island| (0..5).map(|n| marker((island * 10 + n) as f32)).collect())
+            .collect()
+    }
+
+    fn origins(population: &[Marker]) -> Vec<usize> {
+        population.iter().map(|individual| (individual.0[0] / 10.0) as usize).collect()
+    }
+
+    #[test]
+    fn no_migration_before_interval() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3).with_migration(Topology::Ring, 2, 1).unwrap();
+
+        let next = model.evolve(&mut rng, &populations(3));
+        for (island, population) in next.iter().enumerate() {
+            assert_eq!(population.len(), 5);
+            assert!(origins(population).iter().all(|&origin| origin == island));
+        }
+    }
+
+    #[test]
+    fn ring_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3).with_migration(Topology::Ring, 1, 1).unwrap();
+
+        let next = model.evolve(&mut rng, &populations(3));
+        for (island, population) in next.iter().enumerate() {
+            let source = (island + 2) % 3;
+            // the best of the previous island lands in the last slot; the elite stays first
+            assert_eq!(population[0].0[0], (island * 10 + 4) as f32);
+            assert_eq!(population[4].0[0], (source * 10 + 4) as f32);
+            assert!(origins(&population[..4]).iter().all(|&origin| origin == island));
+        }
+    }
+
+    #[test]
+    fn fully_connected_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3)
+            .with_migration(Topology::FullyConnected, 1, 1)
+            .unwrap()
+            .with_migrant_policy(MigrantPolicy::Random);
+
+        let next = model.evolve(&mut rng, &populations(3));
+        for (island, population) in next.iter().enumerate() {
+            let mut foreign: Vec<usize> = origins(population).into_iter().filter(|&origin| origin != island).collect();
+            foreign.sort_unstable();
+            let expected: Vec<usize> = (0..3).filter(|&origin| origin != island).collect();
+            assert_eq!(foreign, expected);
+        }
+    }
+
+    fn foreign(next: &[Vec<Marker>]) -> usize {
+        next.iter()
+            .enumerate()
+            .map(|(island, population)| origins(population).iter().filter(|&&origin| origin != island).count())
+            .sum()
+    }
+
+    #[test]
+    fn random_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3).with_migration(Topology::Random, 1, 2).unwrap();
+
+        let next = model.evolve(&mut rng, &populations(3));
+        // every island sent two migrants to one other island
+        assert_eq!(foreign(&next), 6);
+    }
+
+    #[test]
+    fn migration_never_displaces_elites() {
+        for topology in [Topology::Random, Topology::FullyConnected] {
+            for seed in 0..200 {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                let mut model = model(3).with_migration(topology, 1, 2).unwrap();
+
+                let next = model.evolve(&mut rng, &populations(3));
+                for (island, population) in next.iter().enumerate() {
+                    assert_eq!(population.len(), 5);
+                    assert_eq!(population[0].0[0], (island * 10 + 4) as f32, "{:?}, seed {}", topology, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_migrants() {
+        // four free slots per island: two sources of two migrants fit, three sources do not
+        assert!(model(3).with_migration(Topology::FullyConnected, 1, 2).is_ok());
+        assert_eq!(
+            model(4).with_migration(Topology::FullyConnected, 1, 2).err(),
+            Some(MigrationError::TooManyMigrants { island: 0, arrivals: 6, free: 4 })
+        );
+        assert_eq!(
+            model(4).with_migration(Topology::Random, 1, 2).err(),
+            Some(MigrationError::TooManyMigrants { island: 0, arrivals: 6, free: 4 })
+        );
+        assert!(model(4).with_migration(Topology::Ring, 1, 4).is_ok());
+        assert_eq!(model(2).with_migration(Topology::Ring, 0, 1).err(), Some(MigrationError::ZeroInterval));
+    }
+
+    #[test]
+    fn oversized_arrivals_are_capped() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(4).with_migration(Topology::FullyConnected, 1, 1).unwrap();
+
+        // smaller populations than the model was checked for: three arrivals, two free slots
+        let populations: Vec<Vec<Marker>> = (0..4)
+            .map(|island| (0..3).map(|n| marker((island * 10 + n) as f32)).collect())
+            .collect();
+        let next = model.evolve(&mut rng, &populations);
+        for (island, population) in next.iter().enumerate() {
+            assert_eq!(population[0].0[0], (island * 10 + 2) as f32);
+        }
+        assert_eq!(foreign(&next), 8);
+    }
+}
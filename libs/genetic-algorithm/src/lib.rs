@@ -1,46 +1,88 @@
+mod cma_es;
+mod crossover;
+mod differential;
+mod discrete;
+mod evolver;
+mod island;
+mod map_elites;
+mod mutation;
+mod novelty;
+mod nsga2;
+mod scaling;
+mod speciation;
+mod statistics;
+
+pub use cma_es::*;
+pub use crossover::*;
+pub use differential::*;
+pub use discrete::*;
+pub use evolver::*;
+pub use island::*;
+pub use map_elites::*;
+pub use mutation::*;
+pub use novelty::*;
+pub use nsga2::*;
+pub use scaling::*;
+pub use speciation::*;
+pub use statistics::*;
+
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::seq::SliceRandom;
 use rand::{Rng, RngCore};
 
 
+// Real-valued by default; `bool`, integer or permutation genes work with the operators in `discrete`
 #[derive(Debug, Clone)]
-pub struct Chromosome {
-    genes: Vec<f32>,
+pub struct Chromosome<G = f32> {
+    genes: Vec<G>,
 }
 
-impl Chromosome {
+impl<G> Chromosome<G> {
     pub fn len(&self) -> usize {
         self.genes.len()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &G> {
         self.genes.iter()
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut G> {
         self.genes.iter_mut()
     }
+
+    pub fn as_slice(&self) -> &[G] {
+        &self.genes
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [G] {
+        &mut self.genes
+    }
 }
 
 use std::ops::Index;
 
-impl Index<usize> for Chromosome {
-    type Output = f32;
+impl<G> Index<usize> for Chromosome<G> {
+    type Output = G;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.genes[index]
     }
 }
 
-impl FromIterator<f32> for Chromosome {
-    fn from_iter<I: IntoIterator<Item = f32>>(iter: I) -> Self {
+impl<G> FromIterator<G> for Chromosome<G> {
+    fn from_iter<I: IntoIterator<Item = G>>(iter: I) -> Self {
         Self {
             genes: iter.into_iter().collect(),
         }
     }
 }
 
-impl IntoIterator for Chromosome {
-    type Item = f32;
+impl<G> IntoIterator for Chromosome<G> {
+    type Item = G;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -48,62 +90,160 @@ impl IntoIterator for Chromosome {
     }
 }
 
-pub struct GenericAlgorithm<S> {
+pub struct GenericAlgorithm<S, G = f32> {
     select_method: S,
-    crossover_method: Box<dyn CrossoverMethod>,
-    mutation_method: Box<dyn MutationMethod>,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+    elite_count: usize, // best individuals carried over unchanged each generation
+    generation: usize,
+    best_fitness: Option<f32>,
 }
 
-impl<S> GenericAlgorithm<S> 
+impl<S, G> GenericAlgorithm<S, G> 
 where
     S: SelectionMethod,
+    G: Clone,
 {
     pub fn new(
         select_method: S,
-        crossover_method: impl CrossoverMethod + 'static,
-        mutation_method: impl MutationMethod + 'static,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
     ) -> Self {
         Self { 
             select_method, 
             crossover_method: Box::new(crossover_method),
             mutation_method: Box::new(mutation_method),
+            elite_count: 0,
+            generation: 0,
+            best_fitness: None,
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I> 
+    pub fn with_elite_count(mut self, elite_count: usize) -> Self {
+        self.elite_count = elite_count;
+        self
+    }
+
+    pub fn elite_count(&self) -> usize {
+        self.elite_count
+    }
+
+    // Returns a generation of the same size: the elites first, then the offspring.
+    // No `Statistics` here, so any gene type works; use `evolve_with_statistics` when the genes are numeric.
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I> 
     where 
-        I: Individual
+        I: Individual<Gene = G>
     {
         assert!(!population.is_empty());
+        assert!(self.elite_count <= population.len());
 
-        (0..population.len() - 1)
-        .map(|_| {
-            let parent_a = self.select_method.select(rng, population).chromosome();
-            let parent_b = self.select_method.select(rng, population).chromosome();
+        let mut ranked: Vec<&I> = population.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        self.track_progress(ranked[0].fitness());
+        self.select_method.next_generation(population);
+
+        let elites = ranked
+            .into_iter()
+            .take(self.elite_count)
+            .map(|elite| I::create(elite.chromosome().clone()));
+
+        let child_count = population.len() - self.elite_count;
+        let parents = self.select_method.select_many(rng, population, 2 * child_count);
+
+        let children = parents
+        .chunks_exact(2)
+        .map(|pair| {
+            let parent_a = pair[0].chromosome();
+            let parent_b = pair[1].chromosome();
 
             let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
             
             self.mutation_method.mutate(rng, &mut child);
 
             I::create(child)
-        })
-        .collect()
+        });
 
+        elites.chain(children).collect()
+    }
+
+    // `evolve`, plus the statistics of the evaluated population it was bred from;
+    // `Statistics::new` compares every pair of chromosomes, so this is worth it for logging, not every generation
+    pub fn evolve_with_statistics<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: Individual<Gene = G>,
+        G: NumericGene,
+    {
+        let statistics = Statistics::new(population);
+        (self.evolve(rng, population), statistics)
+    }
+
+    // Lets adaptive mutation methods react to how the best fitness is moving
+    fn track_progress(&mut self, best: f32) {
+        let improved = self.best_fitness.is_some_and(|previous| best > previous);
+        self.best_fitness = Some(self.best_fitness.map_or(best, |previous| previous.max(best)));
+
+        self.mutation_method.adapt(&Progress { generation: self.generation, improved });
+        self.generation += 1;
     }
 }
 
 pub trait Individual{
+    type Gene;
+
     fn fitness(&self) -> f32;
-    fn chromosome(&self) -> &Chromosome;
-    fn create(chromosome: Chromosome) -> Self;
+    fn chromosome(&self) -> &Chromosome<Self::Gene>;
+    fn create(chromosome: Chromosome<Self::Gene>) -> Self;
 }
 
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where 
         I: Individual;
+
+    // All parents of a generation at once; methods like SUS override this to pick them in a single pass
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: Individual
+    {
+        (0..count).map(|_| self.select(rng, population)).collect()
+    }
+
+    // Called by `GenericAlgorithm::evolve` once per generation with the evaluated population, before any parent is selected
+    fn next_generation<I>(&mut self, _population: &[I])
+    where
+        I: Individual
+    {}
+}
+
+// Selection that only looks at one weight per individual, so `ScaledSelection` can feed it scaled fitness
+pub trait WeightedSelection {
+    // `count` indices into `weights`; the weights go through `proportional_weights` first
+    fn select_indices(&self, rng: &mut dyn RngCore, weights: &[f32], count: usize) -> Vec<usize>;
+}
+
+// Fitness used as weights: negative or non-finite values get no share, and when nothing is left
+// (e.g. nobody ate yet) every individual gets the same share
+pub(crate) fn proportional_weights(fitness: impl Iterator<Item = f32>) -> Vec<f32> {
+    let weights: Vec<f32> = fitness
+        .map(|fitness| if fitness.is_finite() { fitness.max(0.0) } else { 0.0 })
+        .collect();
+    let total: f32 = weights.iter().sum();
+    if total > 0.0 && total.is_finite() {
+        weights
+    } else {
+        vec![1.0; weights.len()]
+    }
+}
+
+// `count` indices drawn in proportion to `weights`, see `proportional_weights`; the weights are normalised once for all picks
+pub(crate) fn weighted_indices(rng: &mut dyn RngCore, weights: &[f32], count: usize) -> Vec<usize> {
+    assert!(!weights.is_empty(), "population should not be empty");
+    let distribution = WeightedIndex::new(proportional_weights(weights.iter().copied()))
+        .expect("weights should have been made valid");
+    (0..count).map(|_| distribution.sample(rng)).collect()
 }
 
+// Fitness-proportionate; negative fitness counts as zero, see `FitnessScaling` for better ways to handle it
 pub struct RouletteWheelSelection;
 
 impl SelectionMethod for RouletteWheelSelection {
@@ -111,47 +251,223 @@ impl SelectionMethod for RouletteWheelSelection {
     where
         I: Individual
     {
-        
-        population
-            .choose_weighted(rng, |individual| individual.fitness())
-            .expect("population should not be empty")
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: Individual
+    {
+        let weights: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        self.select_indices(rng, &weights, count)
+            .into_iter()
+            .map(|index| &population[index])
+            .collect()
     }
 }
 
-pub trait CrossoverMethod {
+impl WeightedSelection for RouletteWheelSelection {
+    fn select_indices(&self, rng: &mut dyn RngCore, weights: &[f32], count: usize) -> Vec<usize> {
+        weighted_indices(rng, weights, count)
+    }
+}
+
+// Best of `size` random picks; with `probability` < 1.0 the runner-ups get a geometric share of the wins.
+// Only the fitness order matters, so zero, negative or badly scaled fitnesses are fine.
+pub struct TournamentSelection {
+    size: usize,
+    probability: f32, // 0.0 - 1.0, chance the best contestant wins
+}
+
+impl TournamentSelection {
+    pub fn new(size: usize, probability: f32) -> Self {
+        assert!(size > 0);
+        assert!(probability > 0.0 && probability <= 1.0);
+        Self { size, probability }
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual
+    {
+        let mut contestants: Vec<&I> = (0..self.size)
+            .map(|_| population.choose(rng).expect("population should not be empty"))
+            .collect();
+        contestants.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let last = contestants.len() - 1;
+        for contestant in &contestants[..last] {
+            if rng.gen_bool(self.probability as f64) {
+                return contestant;
+            }
+        }
+        contestants[last]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RankPressure {
+    Linear { pressure: f32 },   // 1.0 - 2.0, expected picks of the best individual per generation
+    Exponential { base: f32 },  // 0.0 - 1.0, each rank down keeps `base` of the weight above it
+}
+
+// Roulette over fitness ranks instead of raw fitness, so a single outlier cannot take over the population
+pub struct RankSelection {
+    pressure: RankPressure,
+}
+
+impl RankSelection {
+    pub fn linear(pressure: f32) -> Self {
+        assert!((1.0..=2.0).contains(&pressure));
+        Self { pressure: RankPressure::Linear { pressure } }
+    }
+
+    pub fn exponential(base: f32) -> Self {
+        assert!(base > 0.0 && base < 1.0);
+        Self { pressure: RankPressure::Exponential { base } }
+    }
+
+    // weight of every individual, in population order
+    fn weights<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual
+    {
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+
+        let worst = (population.len() - 1).max(1) as f32;
+        let mut weights = vec![0.0; population.len()];
+        for (rank, index) in order.into_iter().enumerate() {
+            weights[index] = match self.pressure {
+                RankPressure::Linear { pressure } => {
+                    (2.0 - pressure) + 2.0 * (pressure - 1.0) * rank as f32 / worst
+                }
+                RankPressure::Exponential { base } => base.powi((population.len() - 1 - rank) as i32),
+            };
+        }
+        weights
+    }
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: Individual
+    {
+        assert!(!population.is_empty(), "population should not be empty");
+        weighted_indices(rng, &self.weights(population), count)
+            .into_iter()
+            .map(|index| &population[index])
+            .collect()
+    }
+}
+
+// Fitness-proportionate like roulette, but with evenly spaced pointers so the picks never stray far from the expected counts
+pub struct StochasticUniversalSampling;
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: Individual
+    {
+        let weights: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        self.select_indices(rng, &weights, count)
+            .into_iter()
+            .map(|index| &population[index])
+            .collect()
+    }
+}
+
+impl WeightedSelection for StochasticUniversalSampling {
+    fn select_indices(&self, rng: &mut dyn RngCore, weights: &[f32], count: usize) -> Vec<usize> {
+        assert!(!weights.is_empty(), "population should not be empty");
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut shares = proportional_weights(weights.iter().copied());
+        let mut total: f32 = shares.iter().sum();
+        if total / count as f32 <= 0.0 {
+            // a total too tiny to split into `count` gaps, e.g. only subnormal fitness; same shares for everyone
+            shares = vec![1.0; shares.len()];
+            total = shares.len() as f32;
+        }
+
+        let spacing = total / count as f32;
+        let mut pointer = rng.gen_range(0.0..spacing);
+        let mut cumulative = 0.0;
+        let mut selected = Vec::with_capacity(count);
+
+        for (index, share) in shares.into_iter().enumerate() {
+            cumulative += share;
+            while selected.len() < count && pointer < cumulative {
+                selected.push(index);
+                pointer += spacing;
+            }
+        }
+        // float rounding can leave the last pointer just past the end
+        selected.resize(count, weights.len() - 1);
+
+        // picks come out in population order, so shuffle them before they get paired up
+        selected.shuffle(rng);
+        selected
+    }
+}
+
+pub trait CrossoverMethod<G = f32> {
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome, 
-        parent_b: &Chromosome
-    ) -> Chromosome;
+        parent_a: &Chromosome<G>, 
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G>;
 }
 
 pub struct UniformCrossover;
 
-impl CrossoverMethod for UniformCrossover {
+impl<G: Clone> CrossoverMethod<G> for UniformCrossover {
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome {
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
         assert_eq!(parent_a.len(), parent_b.len());
         
 
         parent_a
             .iter()
             .zip(parent_b.iter())
-            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .map(|(a, b)| if rng.gen_bool(0.5) { a.clone() } else { b.clone() })
             .collect()
 
     }
 }
 
-pub trait MutationMethod {
-    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+pub trait MutationMethod<G = f32> {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>);
+
+    // Called by `GenericAlgorithm::evolve` once per generation, before any child is mutated
+    fn adapt(&mut self, _progress: &Progress) {}
 }
 
+// The step is sign * coeff * uniform(0, 1); see `GaussianMutation` for a real normal deviate
 pub struct GussianMutation {
     chance: f32, // 0.0 - 1.0
     coeff: f32, // 0.0 - 3.0
@@ -159,7 +475,7 @@ pub struct GussianMutation {
 
 impl GussianMutation {
     pub fn new(chance: f32, coeff: f32) -> Self {
-        assert!(chance >= 0.0 && chance <= 1.0);
+        assert!((0.0..=1.0).contains(&chance));
         Self { chance, coeff }
     }
 }
@@ -204,6 +520,8 @@ mod tests {
     }
 
     impl Individual for TestIndividual {
+        type Gene = f32;
+
         fn fitness(&self) -> f32 {
             match self {
                 Self::WithChromosome { chromosome } => {
@@ -237,7 +555,7 @@ mod tests {
 
         let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-        let ga = GenericAlgorithm::new(
+        let mut ga = GenericAlgorithm::new(
             RouletteWheelSelection, 
             UniformCrossover, 
             GussianMutation::new(0.5, 2.0)
@@ -253,15 +571,16 @@ mod tests {
 
         for _ in 0..100 {
             population = ga.evolve(&mut rng, &population);
+            assert_eq!(population.len(), 5);
         }
 
-        // 100 evolve with mutate
+        // 100 evolve with mutate, full-size generations
         let expected_population = vec![
-            individual(vec![0.52645266, 2.4504585, 14.50473, 10.760423, 10.004925]), // 38.24699
-            individual(vec![3.7930882, 3.641621, 16.658728, 9.183177, 10.004925]), // 43.28154
-            individual(vec![0.60514677, 4.4005523, 17.480516, 10.760423, 9.906798]), // 43.15344
-            individual(vec![4.1820974, 1.1582748, 14.313356, 6.3595815, 6.3362103]), // 32.349518
-            individual(vec![3.8659909, 7.108799, 14.457064, 5.6994195, 7.4700184]), // 38.60129
+            individual(vec![12.156078, 5.5136, 5.100667, 10.5591755, 17.353876]), // 50.6834
+            individual(vec![10.921199, 5.2793903, 4.3079534, 12.9988575, 16.244308]), // 49.7517
+            individual(vec![11.894645, 8.018323, 6.705867, 12.037293, 16.244308]), // 54.9004
+            individual(vec![13.220036, 6.792012, 5.721196, 14.537885, 16.369024]), // 56.6402
+            individual(vec![11.450226, 3.9837189, 5.38408, 13.449214, 18.34307]), // 52.6103
         ];
 
         // 100 evolve without mutate
@@ -299,6 +618,35 @@ mod tests {
             
     }
 
+    #[test]
+    fn elitism() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<TestIndividual> = [
+            vec![1.0, 1.0],
+            vec![5.0, 5.0],
+            vec![2.0, 2.0],
+            vec![4.0, 4.0],
+        ]
+            .into_iter()
+            .map(|genes| TestIndividual::create(genes.into_iter().collect()))
+            .collect();
+
+        let mut ga = GenericAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GussianMutation::new(1.0, 2.0),
+        )
+        .with_elite_count(2);
+
+        let (next, statistics) = ga.evolve_with_statistics(&mut rng, &population);
+        assert_eq!(statistics.len(), population.len());
+        assert_eq!(statistics.max(), population[1].fitness());
+        assert_eq!(next.len(), population.len());
+        assert_eq!(next[0], population[1]);
+        assert_eq!(next[1], population[3]);
+        assert_ne!(next[2], population[1]);
+    }
+
     #[test]
     fn uniform_crossover() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
@@ -408,6 +756,95 @@ mod tests {
         }
     }
 
+    fn tournament_histogram(size: usize, probability: f32, population: &[TestIndividual]) -> BTreeMap<i32, i32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let selection = TournamentSelection::new(size, probability);
+        let mut histogram = BTreeMap::new();
+
+        for _ in 0..1000 {
+            let selected_fitness = selection.select(&mut rng, population).fitness() as i32;
+            *histogram.entry(selected_fitness).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    #[test]
+    fn tournament_selection() {
+        let population = vec![
+            TestIndividual::new(3.0),
+            TestIndividual::new(4.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(2.0),
+        ];
+
+        let actual_histogram = tournament_histogram(2, 1.0, &population);
+        // best of two: 1/16, 3/16, 5/16 and 7/16 of the picks
+        let expected_histogram = BTreeMap::from_iter([
+            (1, 70),
+            (2, 175),
+            (3, 302),
+            (4, 453),
+        ]);
+        assert_eq!(actual_histogram, expected_histogram);
+    }
+
+    #[test]
+    fn tournament_selection_with_zero_fitness() {
+        let population = vec![TestIndividual::new(0.0), TestIndividual::new(0.0)];
+        let actual_histogram = tournament_histogram(3, 0.75, &population);
+        assert_eq!(actual_histogram, BTreeMap::from_iter([(0, 1000)]));
+    }
+
+    fn histogram(selected: Vec<&TestIndividual>) -> BTreeMap<i32, i32> {
+        let mut histogram = BTreeMap::new();
+        for individual in selected {
+            *histogram.entry(individual.fitness() as i32).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    #[test]
+    fn rank_selection() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            TestIndividual::new(30.0),
+            TestIndividual::new(4000.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(2.0),
+        ];
+
+        // ranks 0..3 weigh 0.5, 0.8333, 1.1667, 1.5 no matter how far ahead the best one is
+        let linear = histogram(RankSelection::linear(1.5).select_many(&mut rng, &population, 1000));
+        assert_eq!(linear, BTreeMap::from_iter([(1, 127), (2, 191), (30, 294), (4000, 388)]));
+
+        // ranks 0..3 weigh 0.125, 0.25, 0.5, 1.0
+        let exponential = histogram(RankSelection::exponential(0.5).select_many(&mut rng, &population, 1000));
+        assert_eq!(exponential, BTreeMap::from_iter([(1, 71), (2, 128), (30, 250), (4000, 551)]));
+    }
+
+    #[test]
+    fn stochastic_universal_sampling() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            TestIndividual::new(3.0),
+            TestIndividual::new(4.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(2.0),
+        ];
+
+        // evenly spaced pointers hit every individual exactly in proportion to its fitness
+        let actual = histogram(StochasticUniversalSampling.select_many(&mut rng, &population, 10));
+        assert_eq!(actual, BTreeMap::from_iter([(1, 1), (2, 2), (3, 3), (4, 4)]));
+    }
+
+    #[test]
+    fn stochastic_universal_sampling_with_zero_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![TestIndividual::new(0.0), TestIndividual::new(0.0)];
+        let selected = StochasticUniversalSampling.select_many(&mut rng, &population, 4);
+        assert_eq!(selected.len(), 4);
+    }
+
     #[test]
     fn roulette_wheel_selection() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
@@ -432,15 +869,12 @@ mod tests {
             *stat += 1;
         }
 
-        let expected_histogram = BTreeMap::from_iter([
-            (1, 102),
-            (2, 198),
-            (3, 301),
-            (4, 399),
-        ]);
-        // instead of testing a exact number(might differ when population init order is different, we can check if freqency falls into a range)
-        // assert!(900 <= *actual_histogram.get(&1).unwrap_or(&0) && *actual_histogram.get(&1).unwrap_or(&0) <= 1100);
-        assert_eq!(actual_histogram, expected_histogram, "\nwe are testing comparison between \n{:?} \nand \n{:?}", actual_histogram, expected_histogram);
+        // each fitness should get its share of the 10.0 total, give or take about three standard deviations
+        for (fitness, count) in &actual_histogram {
+            let expected = 1000.0 * *fitness as f32 / 10.0;
+            assert!((*count as f32 - expected).abs() <= 50.0, "{:?}", actual_histogram);
+        }
+        assert_eq!(actual_histogram.values().sum::<i32>(), 1000);
     }
 }
 
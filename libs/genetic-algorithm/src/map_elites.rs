@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use rand::{Rng, RngCore};
+
+use crate::*;
+
+// One behaviour dimension of the grid: `bins` equal slices of `low..high`, values outside go to the end bins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Axis {
+    low: f32,
+    high: f32,
+    bins: usize,
+}
+
+impl Axis {
+    pub fn new(low: f32, high: f32, bins: usize) -> Self {
+        assert!(low < high);
+        assert!(bins > 0);
+        Self { low, high, bins }
+    }
+
+    fn bin(&self, value: f32) -> usize {
+        let position = (value - self.low) / (self.high - self.low) * self.bins as f32;
+        (position.max(0.0) as usize).min(self.bins - 1)
+    }
+}
+
+// The best individual found so far for every cell of a grid over behaviour space
+pub struct GridArchive<I> {
+    axes: Vec<Axis>,
+    cells: BTreeMap<Vec<usize>, I>, // ordered, so seeded runs pick the same elites
+}
+
+impl<I> GridArchive<I>
+where
+    I: Behaviour,
+{
+    pub fn new(axes: Vec<Axis>) -> Self {
+        assert!(!axes.is_empty());
+        Self { axes, cells: BTreeMap::new() }
+    }
+
+    // Bin index along every axis
+    pub fn cell(&self, behaviour: &[f32]) -> Vec<usize> {
+        assert_eq!(behaviour.len(), self.axes.len(), "one behaviour value per axis");
+        self.axes.iter().zip(behaviour).map(|(axis, &value)| axis.bin(value)).collect()
+    }
+
+    // Keeps the individual if its cell is empty or it beats the current elite; returns whether it was kept
+    pub fn insert(&mut self, individual: I) -> bool {
+        let cell = self.cell(&individual.behaviour());
+        match self.cells.get(&cell) {
+            Some(elite) if elite.fitness() >= individual.fitness() => false,
+            _ => {
+                self.cells.insert(cell, individual);
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, cell: &[usize]) -> Option<&I> {
+        self.cells.get(cell)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn elites(&self) -> impl Iterator<Item = (&[usize], &I)> {
+        self.cells.iter().map(|(cell, elite)| (cell.as_slice(), elite))
+    }
+
+    pub fn best(&self) -> Option<&I> {
+        self.cells.values().max_by(|a, b| a.fitness().total_cmp(&b.fitness()))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.axes.iter().map(|axis| axis.bins).product()
+    }
+
+    // Share of cells holding an elite, 0.0 - 1.0
+    pub fn coverage(&self) -> f32 {
+        self.len() as f32 / self.capacity() as f32
+    }
+
+    // Sum of the elites' fitness; assumes non-negative fitness, otherwise filling a cell can lower it
+    pub fn qd_score(&self) -> f32 {
+        self.cells.values().map(Individual::fitness).sum()
+    }
+
+    fn random_elite(&self, rng: &mut dyn RngCore) -> &I {
+        let index = rng.gen_range(0..self.cells.len());
+        self.cells.values().nth(index).unwrap()
+    }
+}
+
+// MAP-Elites variation step: children of random elites, bred with the usual operators.
+// The caller evaluates them and hands them to `GridArchive::insert`.
+pub struct MapElites<G = f32> {
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+}
+
+impl<G> MapElites<G>
+where
+    G: Clone,
+{
+    pub fn new(
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+        }
+    }
+
+    pub fn offspring<I>(&self, rng: &mut dyn RngCore, archive: &GridArchive<I>, count: usize) -> Vec<I>
+    where
+        I: Behaviour<Gene = G>
+    {
+        assert!(!archive.is_empty(), "seed the archive with an initial population first");
+
+        (0..count)
+            .map(|_| {
+                let parent_a = archive.random_elite(rng).chromosome();
+                let parent_b = archive.random_elite(rng).chromosome();
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+                I::create(child)
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // behaviour is the two genes; fitness is highest at the centre of the unit square
+    struct Point(Chromosome);
+
+    impl Individual for Point {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            2.0 - (self.0[0] - 0.5).abs() - (self.0[1] - 0.5).abs()
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    impl Behaviour for Point {
+        fn behaviour(&self) -> Vec<f32> {
+            self.0.iter().copied().collect()
+        }
+    }
+
+    fn point(x: f32, y: f32) -> Point {
+        Point(vec![x, y].into_iter().collect())
+    }
+
+    fn archive() -> GridArchive<Point> {
+        GridArchive::new(vec![Axis::new(0.0, 1.0, 5), Axis::new(0.0, 1.0, 5)])
+    }
+
+    #[test]
+    fn cells() {
+        let archive = archive();
+        assert_eq!(archive.cell(&[0.0, 0.99]), vec![0, 4]);
+        assert_eq!(archive.cell(&[0.5, 0.2]), vec![2, 1]);
+        assert_eq!(archive.cell(&[-3.0, 7.0]), vec![0, 4]);
+        assert_eq!(archive.capacity(), 25);
+    }
+
+    #[test]
+    fn insertion_by_improvement() {
+        let mut archive = archive();
+        assert!(archive.insert(point(0.05, 0.05)));
+        assert!(archive.insert(point(0.15, 0.15)));
+        assert!(!archive.insert(point(0.01, 0.01)));
+        assert!(archive.insert(point(0.9, 0.9)));
+
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.get(&[0, 0]).unwrap().0[0], 0.15);
+        assert_relative_eq!(archive.coverage(), 2.0 / 25.0);
+        assert_relative_eq!(archive.qd_score(), 1.3 + 1.2);
+        assert_eq!(archive.best().unwrap().0[0], 0.15);
+    }
+
+    #[test]
+    fn illuminates_the_grid() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let map_elites = MapElites::new(BlxAlphaCrossover::new(0.5), GaussianMutation::new(1.0, 0.1));
+        let mut archive = archive();
+        archive.insert(point(0.5, 0.5));
+
+        let mut previous = archive.qd_score();
+        for _ in 0..40 {
+            for child in map_elites.offspring(&mut rng, &archive, 10) {
+                archive.insert(child);
+            }
+            assert!(archive.qd_score() >= previous);
+            previous = archive.qd_score();
+        }
+
+        assert!(archive.coverage() > 0.9, "{}", archive.coverage());
+        let centre = archive.get(&[2, 2]).unwrap();
+        assert!(centre.fitness() > 1.9);
+    }
+}
@@ -0,0 +1,226 @@
+use std::f32::consts::PI;
+
+use rand::{Rng, RngCore};
+use rand_distr::StandardNormal;
+
+use crate::*;
+
+// What `GenericAlgorithm::evolve` tells the mutation method once per generation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub generation: usize,
+    pub improved: bool, // the best fitness so far went up since the previous generation
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SigmaSchedule {
+    Constant,
+    Linear { end: f32, generations: usize }, // straight line from the initial sigma to `end`, then stays there
+    Exponential { decay: f32 },             // sigma *= decay every generation
+    OneFifth { factor: f32, window: usize }, // Rechenberg: widen if more than 1/5 of the window improved, else narrow
+}
+
+// Mutation step size that follows a `SigmaSchedule`
+#[derive(Debug, Clone)]
+pub struct AdaptiveSigma {
+    initial: f32,
+    current: f32,
+    schedule: SigmaSchedule,
+    successes: usize,
+    seen: usize,
+}
+
+impl AdaptiveSigma {
+    pub fn new(sigma: f32, schedule: SigmaSchedule) -> Self {
+        assert!(sigma >= 0.0);
+        match schedule {
+            SigmaSchedule::Constant => {}
+            SigmaSchedule::Linear { end, .. } => assert!(end >= 0.0),
+            SigmaSchedule::Exponential { decay } => assert!(decay > 0.0 && decay <= 1.0),
+            SigmaSchedule::OneFifth { factor, window } => assert!(factor > 0.0 && factor < 1.0 && window > 0),
+        }
+        Self { initial: sigma, current: sigma, schedule, successes: 0, seen: 0 }
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.current
+    }
+
+    pub fn update(&mut self, progress: &Progress) {
+        match self.schedule {
+            SigmaSchedule::Constant => {}
+            SigmaSchedule::Linear { end, generations } => {
+                let t = (progress.generation as f32 / generations.max(1) as f32).min(1.0);
+                self.current = self.initial + (end - self.initial) * t;
+            }
+            SigmaSchedule::Exponential { decay } => self.current *= decay,
+            SigmaSchedule::OneFifth { factor, window } => {
+                self.seen += 1;
+                self.successes += progress.improved as usize;
+                if self.seen == window {
+                    if self.successes * 5 > window {
+                        self.current /= factor;
+                    } else if self.successes * 5 < window {
+                        self.current *= factor;
+                    }
+                    self.seen = 0;
+                    self.successes = 0;
+                }
+            }
+        }
+    }
+}
+
+// Adds a normal deviate with standard deviation `sigma` to each gene with probability `chance`
+pub struct GaussianMutation {
+    chance: f32, // 0.0 - 1.0
+    sigma: AdaptiveSigma,
+}
+
+impl GaussianMutation {
+    pub fn new(chance: f32, sigma: f32) -> Self {
+        Self::with_schedule(chance, sigma, SigmaSchedule::Constant)
+    }
+
+    pub fn with_schedule(chance: f32, sigma: f32, schedule: SigmaSchedule) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance, sigma: AdaptiveSigma::new(sigma, schedule) }
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma.sigma()
+    }
+}
+
+impl MutationMethod for GaussianMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene += self.sigma.sigma() * rng.sample::<f32, _>(StandardNormal);
+            }
+        }
+    }
+
+    fn adapt(&mut self, progress: &Progress) {
+        self.sigma.update(progress);
+    }
+}
+
+// Like `GaussianMutation` but with Cauchy-distributed steps: mostly small, occasionally very large jumps
+pub struct CauchyMutation {
+    chance: f32, // 0.0 - 1.0
+    scale: AdaptiveSigma,
+}
+
+impl CauchyMutation {
+    pub fn new(chance: f32, scale: f32) -> Self {
+        Self::with_schedule(chance, scale, SigmaSchedule::Constant)
+    }
+
+    pub fn with_schedule(chance: f32, scale: f32, schedule: SigmaSchedule) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance, scale: AdaptiveSigma::new(scale, schedule) }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale.sigma()
+    }
+}
+
+impl MutationMethod for CauchyMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                let u: f32 = rng.gen_range(0.0..1.0);
+                *gene += self.scale.sigma() * (PI * (u - 0.5)).tan();
+            }
+        }
+    }
+
+    fn adapt(&mut self, progress: &Progress) {
+        self.scale.update(progress);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn deltas(method: &impl MutationMethod) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome = vec![0.0; 10_000].into_iter().collect();
+        method.mutate(&mut rng, &mut child);
+        child.into_iter().collect()
+    }
+
+    fn progress(generation: usize, improved: bool) -> Progress {
+        Progress { generation, improved }
+    }
+
+    #[test]
+    fn gaussian_has_requested_sigma() {
+        let deltas = deltas(&GaussianMutation::new(1.0, 0.5));
+        let mean = deltas.iter().sum::<f32>() / deltas.len() as f32;
+        let variance = deltas.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / deltas.len() as f32;
+        assert!(mean.abs() < 0.02);
+        assert!((variance.sqrt() - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn cauchy_has_requested_scale() {
+        // half of a Cauchy distribution lies within ±scale
+        let deltas = deltas(&CauchyMutation::new(1.0, 0.5));
+        let inside = deltas.iter().filter(|d| d.abs() <= 0.5).count() as f32 / deltas.len() as f32;
+        assert!((inside - 0.5).abs() < 0.02);
+        assert!(deltas.iter().any(|d| d.abs() > 10.0));
+    }
+
+    #[test]
+    fn zero_chance_keeps_chromosome() {
+        let deltas = deltas(&GaussianMutation::new(0.0, 0.5));
+        assert!(deltas.iter().all(|&d| d == 0.0));
+    }
+
+    #[test]
+    fn linear_schedule() {
+        let mut sigma = AdaptiveSigma::new(1.0, SigmaSchedule::Linear { end: 0.2, generations: 4 });
+        let actual: Vec<f32> = (1..=6)
+            .map(|generation| {
+                sigma.update(&progress(generation, false));
+                sigma.sigma()
+            })
+            .collect();
+        assert_relative_eq!(actual.as_slice(), [0.8, 0.6, 0.4, 0.2, 0.2, 0.2].as_ref());
+    }
+
+    #[test]
+    fn exponential_schedule() {
+        let mut mutation = GaussianMutation::with_schedule(0.5, 1.0, SigmaSchedule::Exponential { decay: 0.5 });
+        for generation in 0..3 {
+            mutation.adapt(&progress(generation, false));
+        }
+        assert_relative_eq!(mutation.sigma(), 0.125);
+    }
+
+    #[test]
+    fn one_fifth_rule() {
+        let schedule = SigmaSchedule::OneFifth { factor: 0.5, window: 5 };
+        let mut sigma = AdaptiveSigma::new(1.0, schedule);
+
+        // 2 out of 5 improved: more than a fifth, so search wider
+        for (generation, improved) in [true, false, true, false, false].into_iter().enumerate() {
+            sigma.update(&progress(generation, improved));
+        }
+        assert_relative_eq!(sigma.sigma(), 2.0);
+
+        // nothing improved: search narrower
+        for generation in 5..10 {
+            sigma.update(&progress(generation, false));
+        }
+        assert_relative_eq!(sigma.sigma(), 1.0);
+    }
+}
@@ -0,0 +1,286 @@
+use rand::RngCore;
+
+use crate::*;
+
+// What an individual did rather than how well it did, e.g. where it ended up or how it turned
+pub trait Behaviour: Individual {
+    fn behaviour(&self) -> Vec<f32>;
+}
+
+// Behaviours that were novel when they were seen; novelty is measured against these and the current population
+#[derive(Debug, Clone)]
+pub struct NoveltyArchive {
+    behaviours: Vec<Vec<f32>>,
+    neighbours: usize, // k of the k-nearest-neighbour average
+    threshold: f32,    // novelty needed to enter the archive
+    capacity: usize,   // oldest entries are dropped beyond this
+}
+
+impl NoveltyArchive {
+    pub fn new(neighbours: usize, threshold: f32) -> Self {
+        assert!(neighbours > 0);
+        assert!(threshold >= 0.0);
+        Self { behaviours: Vec::new(), neighbours, threshold, capacity: usize::MAX }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0);
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviours.is_empty()
+    }
+
+    pub fn behaviours(&self) -> &[Vec<f32>] {
+        &self.behaviours
+    }
+
+    // Mean distance from each behaviour to its nearest neighbours among the others and the archive, in input order
+    pub fn novelty(&self, behaviours: &[Vec<f32>]) -> Vec<f32> {
+        behaviours
+            .iter()
+            .enumerate()
+            .map(|(index, behaviour)| {
+                let mut distances: Vec<f32> = behaviours
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, other)| other)
+                    .chain(&self.behaviours)
+                    .map(|other| distance(behaviour, other))
+                    .collect();
+                if distances.is_empty() {
+                    return 0.0;
+                }
+                distances.sort_by(f32::total_cmp);
+                distances.truncate(self.neighbours);
+                distances.iter().sum::<f32>() / distances.len() as f32
+            })
+            .collect()
+    }
+
+    // Archives every behaviour whose novelty reached the threshold
+    pub fn record(&mut self, behaviours: &[Vec<f32>], novelty: &[f32]) {
+        assert_eq!(behaviours.len(), novelty.len());
+        for (behaviour, &novelty) in behaviours.iter().zip(novelty) {
+            if novelty >= self.threshold {
+                self.behaviours.push(behaviour.clone());
+            }
+        }
+        if self.behaviours.len() > self.capacity {
+            let excess = self.behaviours.len() - self.capacity;
+            self.behaviours.drain(..excess);
+        }
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+}
+
+// Rescaled to 0.0 - 1.0 so novelty and fitness can be mixed; all zeros when every value is the same
+fn normalise(values: &[f32]) -> Vec<f32> {
+    let low = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let high = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let span = high - low;
+    values
+        .iter()
+        .map(|value| if span > 0.0 { (value - low) / span } else { 0.0 })
+        .collect()
+}
+
+// Runs a `GenericAlgorithm` on novelty instead of fitness, optionally blended with fitness:
+// score = (1 - fitness_weight) * novelty + fitness_weight * fitness, both rescaled to 0.0 - 1.0
+pub struct NoveltySearch<S, G = f32> {
+    algorithm: GenericAlgorithm<S, G>,
+    archive: NoveltyArchive,
+    fitness_weight: f32,
+}
+
+impl<S, G> NoveltySearch<S, G>
+where
+    S: SelectionMethod,
+    G: Clone,
+{
+    pub fn new(algorithm: GenericAlgorithm<S, G>, archive: NoveltyArchive) -> Self {
+        Self { algorithm, archive, fitness_weight: 0.0 }
+    }
+
+    pub fn with_fitness_weight(mut self, fitness_weight: f32) -> Self {
+        assert!((0.0..=1.0).contains(&fitness_weight));
+        self.fitness_weight = fitness_weight;
+        self
+    }
+
+    pub fn archive(&self) -> &NoveltyArchive {
+        &self.archive
+    }
+
+    // What selection sees for each individual, in population order
+    pub fn scores<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Behaviour<Gene = G>
+    {
+        let behaviours: Vec<Vec<f32>> = population.iter().map(Behaviour::behaviour).collect();
+        self.blend(population, &self.archive.novelty(&behaviours))
+    }
+
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    where
+        I: Behaviour<Gene = G>
+    {
+        let behaviours: Vec<Vec<f32>> = population.iter().map(Behaviour::behaviour).collect();
+        let novelty = self.archive.novelty(&behaviours);
+        let scores = self.blend(population, &novelty);
+        self.archive.record(&behaviours, &novelty);
+
+        let scored: Vec<Scored<G>> = population
+            .iter()
+            .zip(scores)
+            .map(|(individual, score)| Scored { chromosome: individual.chromosome().clone(), score })
+            .collect();
+
+        self.algorithm
+            .evolve(rng, &scored)
+            .into_iter()
+            .map(|child| I::create(child.chromosome))
+            .collect()
+    }
+
+    fn blend<I>(&self, population: &[I], novelty: &[f32]) -> Vec<f32>
+    where
+        I: Behaviour<Gene = G>
+    {
+        if self.fitness_weight == 0.0 {
+            return novelty.to_vec();
+        }
+        let fitness: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        normalise(novelty)
+            .into_iter()
+            .zip(normalise(&fitness))
+            .map(|(novelty, fitness)| (1.0 - self.fitness_weight) * novelty + self.fitness_weight * fitness)
+            .collect()
+    }
+}
+
+// A chromosome carrying its novelty score as fitness, so the wrapped algorithm's selection runs on novelty
+struct Scored<G> {
+    chromosome: Chromosome<G>,
+    score: f32,
+}
+
+impl<G> Individual for Scored<G> {
+    type Gene = G;
+
+    fn fitness(&self) -> f32 {
+        self.score
+    }
+
+    fn chromosome(&self) -> &Chromosome<G> {
+        &self.chromosome
+    }
+
+    fn create(chromosome: Chromosome<G>) -> Self {
+        Self { chromosome, score: 0.0 }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // the behaviour is the chromosome itself; fitness rewards staying near the origin
+    struct Walker(Chromosome);
+
+    impl Individual for Walker {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            -self.0.iter().map(|gene| gene.abs()).sum::<f32>()
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    impl Behaviour for Walker {
+        fn behaviour(&self) -> Vec<f32> {
+            self.0.iter().copied().collect()
+        }
+    }
+
+    fn walkers(count: usize) -> Vec<Walker> {
+        (0..count).map(|_| Walker(vec![0.0, 0.0].into_iter().collect())).collect()
+    }
+
+    fn search(fitness_weight: f32) -> NoveltySearch<TournamentSelection> {
+        let algorithm = GenericAlgorithm::new(TournamentSelection::new(2, 1.0), UniformCrossover, GaussianMutation::new(1.0, 0.2));
+        NoveltySearch::new(algorithm, NoveltyArchive::new(3, 0.5)).with_fitness_weight(fitness_weight)
+    }
+
+    fn spread(population: &[Walker]) -> f32 {
+        population.iter().map(|walker| -walker.fitness()).sum::<f32>() / population.len() as f32
+    }
+
+    #[test]
+    fn novelty_is_mean_nearest_distance() {
+        let mut archive = NoveltyArchive::new(2, 1.5);
+        let behaviours = vec![vec![0.0], vec![1.0], vec![3.0]];
+        let novelty = archive.novelty(&behaviours);
+        assert_relative_eq!(novelty.as_slice(), [2.0, 1.5, 2.5].as_ref());
+
+        archive.record(&behaviours, &novelty);
+        assert_eq!(archive.len(), 3);
+        let novelty = archive.novelty(&[vec![3.0]]);
+        assert_relative_eq!(novelty.as_slice(), [1.0].as_ref());
+    }
+
+    #[test]
+    fn archive_capacity() {
+        let mut archive = NoveltyArchive::new(1, 0.0).with_capacity(2);
+        archive.record(&[vec![1.0], vec![2.0], vec![3.0]], &[1.0, 1.0, 1.0]);
+        assert_eq!(archive.behaviours(), &[vec![2.0], vec![3.0]]);
+    }
+
+    #[test]
+    fn novelty_spreads_out_where_fitness_does_not() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut novelty = search(0.0);
+        let mut fitness = search(1.0);
+
+        let mut explorers = walkers(20);
+        let mut exploiters = walkers(20);
+        for _ in 0..30 {
+            explorers = novelty.evolve(&mut rng, &explorers);
+            exploiters = fitness.evolve(&mut rng, &exploiters);
+        }
+
+        assert!(!novelty.archive().is_empty());
+        assert!(spread(&explorers) > 2.0 * spread(&exploiters), "{} vs {}", spread(&explorers), spread(&exploiters));
+    }
+
+    #[test]
+    fn blended_scores() {
+        let population: Vec<Walker> = [0.0, 1.0, 3.0].iter().map(|&x| Walker(vec![x, 0.0].into_iter().collect())).collect();
+        // novelty 1.0, 1.0, 2.0 against the other two -> 0, 0, 1; fitness 0, -1, -3 -> 1, 2/3, 0
+        let search = NoveltySearch { archive: NoveltyArchive::new(1, 0.5), ..search(0.5) };
+        let scores = search.scores(&population);
+        assert_relative_eq!(scores.as_slice(), [0.5, 1.0 / 3.0, 0.5].as_ref());
+    }
+}
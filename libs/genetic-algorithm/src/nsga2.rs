@@ -0,0 +1,269 @@
+use std::cmp::Ordering;
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use crate::*;
+
+// Several objectives instead of one fitness, all maximised (negate anything that should be minimised)
+pub trait MultiObjective: Individual {
+    fn objectives(&self) -> Vec<f32>;
+}
+
+// Every objective at least as good and at least one strictly better
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+// Indices grouped into Pareto fronts, best front first
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated_by = vec![0; n];
+    let mut dominating: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+    for a in 0..n {
+        for b in a + 1..n {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominating[a].push(b);
+                dominated_by[b] += 1;
+            } else if dominates(&objectives[b], &objectives[a]) {
+                dominating[b].push(a);
+                dominated_by[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|&index| dominated_by[index] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &index in &front {
+            for &other in &dominating[index] {
+                dominated_by[other] -= 1;
+                if dominated_by[other] == 0 {
+                    next.push(other);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+// How isolated each member of `front` is, in `front` order; the extremes of every objective get infinity
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distance = vec![0.0; front.len()];
+    if front.len() < 3 {
+        distance.fill(f32::INFINITY);
+        return distance;
+    }
+
+    // one column of values per objective, in `front` order
+    let columns = (0..objectives[front[0]].len())
+        .map(|objective| front.iter().map(|&index| objectives[index][objective]).collect::<Vec<f32>>());
+
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    for values in columns {
+        let value = |position: usize| values[position];
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distance[first] = f32::INFINITY;
+        distance[last] = f32::INFINITY;
+
+        let span = value(last) - value(first);
+        if span == 0.0 {
+            continue;
+        }
+        for window in order.windows(3) {
+            distance[window[1]] += (value(window[2]) - value(window[0])) / span;
+        }
+    }
+    distance
+}
+
+// The members of the first Pareto front
+pub fn pareto_front<I>(population: &[I]) -> Vec<&I>
+where
+    I: MultiObjective
+{
+    let objectives: Vec<Vec<f32>> = population.iter().map(|individual| individual.objectives()).collect();
+    non_dominated_sort(&objectives)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|index| &population[index])
+        .collect()
+}
+
+// (front, crowding distance) per individual, in population order
+fn rank<I>(population: &[I]) -> Vec<(usize, f32)>
+where
+    I: MultiObjective
+{
+    let objectives: Vec<Vec<f32>> = population.iter().map(|individual| individual.objectives()).collect();
+    let mut ranks = vec![(0, 0.0); population.len()];
+
+    for (front_index, front) in non_dominated_sort(&objectives).into_iter().enumerate() {
+        for (&index, distance) in front.iter().zip(crowding_distance(&objectives, &front)) {
+            ranks[index] = (front_index, distance);
+        }
+    }
+    ranks
+}
+
+// lower front wins, then the less crowded one
+fn crowded_compare(a: (usize, f32), b: (usize, f32)) -> Ordering {
+    a.0.cmp(&b.0).then_with(|| b.1.total_cmp(&a.1))
+}
+
+// NSGA-II split in two so the caller can evaluate the offspring in between:
+// `offspring` breeds a new batch, `survivors` keeps the best of parents + evaluated offspring
+pub struct Nsga2<G = f32> {
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+}
+
+impl<G> Nsga2<G>
+where
+    G: Clone,
+{
+    pub fn new(
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+        }
+    }
+
+    // As many children as there are parents, parents picked by binary crowded tournament
+    pub fn offspring<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    where
+        I: MultiObjective<Gene = G>
+    {
+        assert!(!population.is_empty());
+        let ranks = rank(population);
+        let indices: Vec<usize> = (0..population.len()).collect();
+
+        let tournament = |rng: &mut dyn RngCore| {
+            let a = *indices.choose(rng).unwrap();
+            let b = *indices.choose(rng).unwrap();
+            let winner = if crowded_compare(ranks[a], ranks[b]) == Ordering::Greater { b } else { a };
+            population[winner].chromosome()
+        };
+
+        (0..population.len())
+            .map(|_| {
+                let parent_a = tournament(rng);
+                let parent_b = tournament(rng);
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+                I::create(child)
+            })
+            .collect()
+    }
+
+    // Fills `size` slots front by front, breaking the last partial front by crowding distance
+    pub fn survivors<I>(&self, combined: Vec<I>, size: usize) -> Vec<I>
+    where
+        I: MultiObjective<Gene = G>
+    {
+        let ranks = rank(&combined);
+        let mut order: Vec<usize> = (0..combined.len()).collect();
+        order.sort_by(|&a, &b| crowded_compare(ranks[a], ranks[b]));
+        order.truncate(size);
+        order.sort_unstable();
+
+        let mut order = order.into_iter().peekable();
+        combined
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, individual)| order.next_if_eq(&index).map(|_| individual))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    // Schaffer's problem: minimise x² and (x - 2)², the Pareto set is 0 <= x <= 2
+    struct Schaffer(Chromosome);
+
+    impl Individual for Schaffer {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.objectives().iter().sum()
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    impl MultiObjective for Schaffer {
+        fn objectives(&self) -> Vec<f32> {
+            let x = self.0[0];
+            vec![-x * x, -(x - 2.0).powi(2)]
+        }
+    }
+
+    #[test]
+    fn dominance() {
+        assert!(dominates(&[1.0, 2.0], &[1.0, 1.0]));
+        assert!(!dominates(&[1.0, 1.0], &[1.0, 1.0]));
+        assert!(!dominates(&[2.0, 0.0], &[0.0, 2.0]));
+    }
+
+    #[test]
+    fn sorting_and_crowding() {
+        let objectives = vec![
+            vec![1.0, 4.0],
+            vec![2.0, 3.0],
+            vec![1.0, 1.0],
+            vec![4.0, 1.0],
+            vec![3.0, 2.5],
+        ];
+        assert_eq!(non_dominated_sort(&objectives), vec![vec![0, 1, 3, 4], vec![2]]);
+
+        let distance = crowding_distance(&objectives, &[0, 1, 3, 4]);
+        assert_eq!(distance[0], f32::INFINITY);
+        assert_eq!(distance[2], f32::INFINITY);
+        assert_relative_eq!(distance[1], 2.0 / 3.0 + 1.5 / 3.0);
+        assert_relative_eq!(distance[3], 2.0 / 3.0 + 2.0 / 3.0);
+    }
+
+    #[test]
+    fn converges_to_pareto_set() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let nsga2 = Nsga2::new(BlxAlphaCrossover::new(0.5), GaussianMutation::new(0.3, 0.1));
+
+        let mut population: Vec<Schaffer> = (0..20)
+            .map(|_| Schaffer::create(vec![rng.gen_range(-10.0..10.0)].into_iter().collect()))
+            .collect();
+
+        for _ in 0..30 {
+            let mut combined = nsga2.offspring(&mut rng, &population);
+            combined.append(&mut population);
+            population = nsga2.survivors(combined, 20);
+        }
+
+        assert_eq!(population.len(), 20);
+        assert_eq!(pareto_front(&population).len(), 20);
+        assert!(population.iter().all(|individual| (-0.05..=2.05).contains(&individual.0[0])));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+
+use rand::RngCore;
+
+use crate::*;
+
+// Rewrites a population's fitness before selection sees it; matters for fitness-proportionate methods,
+// which cannot use negative fitness and lose all pressure once everyone scores about the same
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitnessScaling {
+    OffsetToMin,                     // f - worst, so the worst gets nothing and the rest keep their differences
+    Sigma { c: f32 },                // f - (mean - c * std_dev), floored at 0; c is usually 2.0
+    Boltzmann { temperature: f32 },  // exp((f - best) / temperature); low temperature means strong pressure
+    Windowing { window: usize },     // f - worst of the last `window` generations, floored at 0
+}
+
+// Runs a weight-based selection method (roulette, SUS) on scaled fitness instead of raw fitness
+pub struct ScaledSelection<S> {
+    method: S,
+    scaling: FitnessScaling,
+    worst: VecDeque<f32>, // worst fitness of the recent generations, for windowing
+}
+
+impl<S> ScaledSelection<S>
+where
+    S: WeightedSelection,
+{
+    pub fn new(method: S, scaling: FitnessScaling) -> Self {
+        match scaling {
+            FitnessScaling::OffsetToMin => {}
+            FitnessScaling::Sigma { c } => assert!(c >= 0.0),
+            FitnessScaling::Boltzmann { temperature } => assert!(temperature > 0.0),
+            FitnessScaling::Windowing { window } => assert!(window > 0),
+        }
+        Self { method, scaling, worst: VecDeque::new() }
+    }
+
+    // Scaled fitness of every individual, in population order
+    pub fn scale(&self, fitness: &[f32]) -> Vec<f32> {
+        if fitness.is_empty() {
+            return Vec::new();
+        }
+        let size = fitness.len() as f32;
+        let worst = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+        let best = fitness.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        match self.scaling {
+            FitnessScaling::OffsetToMin => fitness.iter().map(|f| f - worst).collect(),
+            FitnessScaling::Sigma { c } => {
+                let mean = fitness.iter().sum::<f32>() / size;
+                let std_dev = (fitness.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / size).sqrt();
+                fitness.iter().map(|f| (f - (mean - c * std_dev)).max(0.0)).collect()
+            }
+            FitnessScaling::Boltzmann { temperature } => {
+                fitness.iter().map(|f| ((f - best) / temperature).exp()).collect()
+            }
+            FitnessScaling::Windowing { .. } => {
+                let floor = self.worst.iter().copied().fold(worst, f32::min);
+                fitness.iter().map(|f| (f - floor).max(0.0)).collect()
+            }
+        }
+    }
+}
+
+impl<S> SelectionMethod for ScaledSelection<S>
+where
+    S: WeightedSelection,
+{
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: Individual
+    {
+        let fitness: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        self.method
+            .select_indices(rng, &self.scale(&fitness), count)
+            .into_iter()
+            .map(|index| &population[index])
+            .collect()
+    }
+
+    // Windowing remembers the worst fitness of every generation it is told about, and forgets it `window` generations later
+    fn next_generation<I>(&mut self, population: &[I])
+    where
+        I: Individual
+    {
+        if let FitnessScaling::Windowing { window } = self.scaling {
+            let worst = population.iter().map(Individual::fitness).fold(f32::INFINITY, f32::min);
+            self.worst.push_back(worst);
+            while self.worst.len() > window {
+                self.worst.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    struct Scored(f32, Chromosome);
+
+    impl Individual for Scored {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.0
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.1
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(0.0, chromosome)
+        }
+    }
+
+    fn population(fitness: &[f32]) -> Vec<Scored> {
+        fitness.iter().map(|&fitness| Scored(fitness, Chromosome::from_iter([fitness]))).collect()
+    }
+
+    fn counts(selected: Vec<&Scored>, population: &[Scored]) -> Vec<usize> {
+        population
+            .iter()
+            .map(|individual| selected.iter().filter(|&&pick| std::ptr::eq(pick, individual)).count())
+            .collect()
+    }
+
+    #[test]
+    fn offset_to_min() {
+        let scaled = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::OffsetToMin).scale(&[-3.0, -1.0, 2.0]);
+        assert_relative_eq!(scaled.as_slice(), [0.0, 2.0, 5.0].as_ref());
+    }
+
+    #[test]
+    fn sigma_scaling() {
+        // mean 2, std_dev 2
+        let scaled = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::Sigma { c: 1.0 }).scale(&[0.0, 0.0, 4.0, 4.0]);
+        assert_relative_eq!(scaled.as_slice(), [0.0, 0.0, 4.0, 4.0].as_ref());
+        let scaled = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::Sigma { c: 0.5 }).scale(&[0.0, 0.0, 4.0, 4.0]);
+        assert_relative_eq!(scaled.as_slice(), [0.0, 0.0, 3.0, 3.0].as_ref());
+    }
+
+    #[test]
+    fn boltzmann_scaling() {
+        let scaled = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::Boltzmann { temperature: 2.0 }).scale(&[-100.0, 4.0, 6.0]);
+        let expected = [(-53.0f32).exp(), (-1.0f32).exp(), 1.0];
+        assert_relative_eq!(scaled.as_slice(), expected.as_ref());
+    }
+
+    #[test]
+    fn windowing() {
+        let mut selection = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::Windowing { window: 2 });
+        let scaled: Vec<Vec<f32>> = [[1.0, 3.0], [5.0, 6.0], [5.0, 6.0]]
+            .iter()
+            .map(|fitness| {
+                selection.next_generation(&population(fitness));
+                selection.scale(fitness)
+            })
+            .collect();
+        // the worst of the previous generation still sets the floor, until it falls out of the window
+        assert_eq!(scaled, vec![vec![0.0, 2.0], vec![4.0, 5.0], vec![0.0, 1.0]]);
+
+        // selecting does not move the window, only `next_generation` does
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population(&[7.0, 8.0]);
+        selection.select_many(&mut rng, &population, 10);
+        assert_eq!(selection.scale(&[7.0, 8.0]), vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn roulette_on_negative_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population(&[-10.0, -5.0, -1.0]);
+
+        // unscaled, negative fitness carries no weight: uniform
+        let selected = RouletteWheelSelection.select_many(&mut rng, &population, 3000);
+        assert!(counts(selected, &population).iter().all(|&count| (900..1100).contains(&count)));
+
+        // offset to min: shares 0, 5 and 9
+        let selection = ScaledSelection::new(RouletteWheelSelection, FitnessScaling::OffsetToMin);
+        let counts = counts(selection.select_many(&mut rng, &population, 1400), &population);
+        assert_eq!(counts[0], 0);
+        assert!((450..550).contains(&counts[1]), "{:?}", counts);
+        assert!((850..950).contains(&counts[2]), "{:?}", counts);
+    }
+
+    #[test]
+    fn selection_falls_back_to_uniform() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        for fitness in [[0.0, 0.0, 0.0], [-1.0, -2.0, -3.0], [f32::NAN, 0.0, 0.0]] {
+            let population = population(&fitness);
+            let roulette = counts(RouletteWheelSelection.select_many(&mut rng, &population, 3000), &population);
+            let sus = counts(StochasticUniversalSampling.select_many(&mut rng, &population, 3000), &population);
+            assert!(roulette.iter().all(|&count| (900..1100).contains(&count)), "{:?}", roulette);
+            // evenly spaced pointers, off by one at most from float rounding
+            assert!(sus.iter().all(|&count| (999..=1001).contains(&count)), "{:?}", sus);
+        }
+
+        // every individual scales to zero when they are all equal
+        let population = population(&[2.0, 2.0]);
+        let selection = ScaledSelection::new(StochasticUniversalSampling, FitnessScaling::OffsetToMin);
+        assert_eq!(counts(selection.select_many(&mut rng, &population, 10), &population), vec![5, 5]);
+    }
+
+    #[test]
+    fn sus_on_subnormal_total() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        // the total rounds to zero once split into pointer gaps
+        let tiny = f32::from_bits(1);
+        let single = population(&[tiny]);
+        assert_eq!(counts(StochasticUniversalSampling.select_many(&mut rng, &single, 2), &single), vec![2]);
+
+        let pair = population(&[tiny, 0.0]);
+        assert_eq!(counts(StochasticUniversalSampling.select_many(&mut rng, &pair, 4), &pair), vec![2, 2]);
+    }
+
+    #[test]
+    fn windowing_in_generic_algorithm() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut ga = GenericAlgorithm::new(
+            ScaledSelection::new(RouletteWheelSelection, FitnessScaling::Windowing { window: 3 }),
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        );
+
+        // two generations bred from the same evaluated population
+        let population = population(&[-2.0, 4.0]);
+        for _ in 0..2 {
+            let children = ga.evolve(&mut rng, &population);
+            assert_eq!(children.len(), 2);
+        }
+        // one entry per evolved generation, however many parents were picked
+        assert_eq!(ga.select_method.worst.len(), 2);
+    }
+}
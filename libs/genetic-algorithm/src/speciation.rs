@@ -0,0 +1,355 @@
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use crate::*;
+
+// How far apart two chromosomes are; plain closures work too
+pub trait DistanceMetric<G = f32> {
+    fn distance(&self, a: &Chromosome<G>, b: &Chromosome<G>) -> f32;
+}
+
+impl<G, F> DistanceMetric<G> for F
+where
+    F: Fn(&Chromosome<G>, &Chromosome<G>) -> f32,
+{
+    fn distance(&self, a: &Chromosome<G>, b: &Chromosome<G>) -> f32 {
+        self(a, b)
+    }
+}
+
+pub struct EuclideanDistance;
+
+impl DistanceMetric for EuclideanDistance {
+    fn distance(&self, a: &Chromosome, b: &Chromosome) -> f32 {
+        assert_eq!(a.len(), b.len());
+        a.iter().zip(b.iter()).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+    }
+}
+
+// Number of positions whose genes differ
+pub struct HammingDistance;
+
+impl<G: PartialEq> DistanceMetric<G> for HammingDistance {
+    fn distance(&self, a: &Chromosome<G>, b: &Chromosome<G>) -> f32 {
+        assert_eq!(a.len(), b.len());
+        a.iter().zip(b.iter()).filter(|(a, b)| a != b).count() as f32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Species<G = f32> {
+    id: usize, // stable across generations
+    representative: Chromosome<G>,
+    members: Vec<usize>, // indices into the population last passed to `speciate`
+    best_fitness: f32,
+    stagnant_for: usize, // generations since `best_fitness` last went up
+}
+
+impl<G> Species<G> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    pub fn best_fitness(&self) -> f32 {
+        self.best_fitness
+    }
+
+    pub fn stagnant_for(&self) -> usize {
+        self.stagnant_for
+    }
+}
+
+// NEAT-style speciation: individuals join the first species whose representative is within `threshold`,
+// offspring are shared out by each species' fitness divided by its size, and species that stop improving die out.
+pub struct Speciation<G = f32> {
+    metric: Box<dyn DistanceMetric<G>>,
+    threshold: f32,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+    stagnation_limit: usize,
+    survival_rate: f32, // 0.0 - 1.0, share of each species allowed to breed
+    species: Vec<Species<G>>,
+    next_id: usize,
+}
+
+impl<G> Speciation<G>
+where
+    G: Clone,
+{
+    pub fn new(
+        metric: impl DistanceMetric<G> + 'static,
+        threshold: f32,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
+    ) -> Self {
+        assert!(threshold >= 0.0);
+        Self {
+            metric: Box::new(metric),
+            threshold,
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            stagnation_limit: 15,
+            survival_rate: 0.5,
+            species: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn with_stagnation_limit(mut self, stagnation_limit: usize) -> Self {
+        assert!(stagnation_limit > 0);
+        self.stagnation_limit = stagnation_limit;
+        self
+    }
+
+    pub fn with_survival_rate(mut self, survival_rate: f32) -> Self {
+        assert!(survival_rate > 0.0 && survival_rate <= 1.0);
+        self.survival_rate = survival_rate;
+        self
+    }
+
+    pub fn species(&self) -> &[Species<G>] {
+        &self.species
+    }
+
+    // Sorts the population into species, keeping last generation's species where they still have members
+    pub fn speciate<I>(&mut self, rng: &mut dyn RngCore, population: &[I])
+    where
+        I: Individual<Gene = G>
+    {
+        for species in &mut self.species {
+            species.members.clear();
+        }
+
+        for (index, individual) in population.iter().enumerate() {
+            let chromosome = individual.chromosome();
+            let metric = &self.metric;
+            let threshold = self.threshold;
+            match self
+                .species
+                .iter_mut()
+                .find(|species| metric.distance(&species.representative, chromosome) <= threshold)
+            {
+                Some(species) => species.members.push(index),
+                None => {
+                    self.species.push(Species {
+                        id: self.next_id,
+                        representative: chromosome.clone(),
+                        members: vec![index],
+                        best_fitness: f32::NEG_INFINITY,
+                        stagnant_for: 0,
+                    });
+                    self.next_id += 1;
+                }
+            }
+        }
+        self.species.retain(|species| !species.members.is_empty());
+
+        for species in &mut self.species {
+            let best = species
+                .members
+                .iter()
+                .map(|&index| population[index].fitness())
+                .fold(f32::NEG_INFINITY, f32::max);
+            if best > species.best_fitness {
+                species.best_fitness = best;
+                species.stagnant_for = 0;
+            } else {
+                species.stagnant_for += 1;
+            }
+            // a random current member stands for the species next generation
+            let representative = *species.members.choose(rng).unwrap();
+            species.representative = population[representative].chromosome().clone();
+        }
+    }
+
+    // Explicit fitness sharing: each fitness divided by the size of its species, in population order.
+    // Expects `speciate` to have been called on the same population.
+    pub fn shared_fitness<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual<Gene = G>
+    {
+        let mut shared = vec![0.0; population.len()];
+        for species in &self.species {
+            for &index in &species.members {
+                shared[index] = population[index].fitness() / species.members.len() as f32;
+            }
+        }
+        shared
+    }
+
+    // Speciates, then breeds a generation of the same size species by species: the champion first, then
+    // children of the top `survival_rate` members. Stagnant species get no offspring unless they hold the best individual.
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    where
+        I: Individual<Gene = G>
+    {
+        assert!(!population.is_empty());
+        self.speciate(rng, population);
+
+        // shifted only when needed to keep every share non-negative
+        let lowest = population.iter().map(Individual::fitness).fold(0.0, f32::min);
+        let best = population.iter().map(Individual::fitness).fold(f32::NEG_INFINITY, f32::max);
+        let shares: Vec<f32> = self
+            .species
+            .iter()
+            .map(|species| {
+                let stagnant = species.stagnant_for >= self.stagnation_limit && species.best_fitness < best;
+                if stagnant {
+                    return 0.0;
+                }
+                let total: f32 = species.members.iter().map(|&index| population[index].fitness() - lowest).sum();
+                total / species.members.len() as f32
+            })
+            .collect();
+        let counts = allocate(&shares, population.len());
+
+        let mut children = Vec::with_capacity(population.len());
+        for (species, count) in self.species.iter().zip(counts) {
+            if count == 0 {
+                continue;
+            }
+            let mut ranked: Vec<&I> = species.members.iter().map(|&index| &population[index]).collect();
+            ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+            let parents = &ranked[..((ranked.len() as f32 * self.survival_rate).ceil() as usize).max(1)];
+
+            children.push(I::create(ranked[0].chromosome().clone()));
+            for _ in 1..count {
+                let parent_a = parents.choose(rng).unwrap().chromosome();
+                let parent_b = parents.choose(rng).unwrap().chromosome();
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+                children.push(I::create(child));
+            }
+        }
+        children
+    }
+}
+
+// Splits `total` slots in proportion to `shares` by largest remainder; equal split when every share is zero
+fn allocate(shares: &[f32], total: usize) -> Vec<usize> {
+    let sum: f32 = shares.iter().sum();
+    let exact: Vec<f32> = if sum > 0.0 {
+        shares.iter().map(|share| share / sum * total as f32).collect()
+    } else {
+        vec![total as f32 / shares.len() as f32; shares.len()]
+    };
+
+    let mut counts: Vec<usize> = exact.iter().map(|value| value.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+    by_remainder.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+
+    let missing = total - counts.iter().sum::<usize>();
+    for &index in by_remainder.iter().take(missing) {
+        counts[index] += 1;
+    }
+    counts
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // two genes; fitness is the first one
+    struct Point(Chromosome);
+
+    impl Individual for Point {
+        type Gene = f32;
+
+        fn fitness(&self) -> f32 {
+            self.0[0]
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    fn point(x: f32, y: f32) -> Point {
+        Point(vec![x, y].into_iter().collect())
+    }
+
+    fn speciation() -> Speciation {
+        Speciation::new(EuclideanDistance, 1.0, UniformCrossover, GaussianMutation::new(0.0, 0.0))
+    }
+
+    #[test]
+    fn distance_metrics() {
+        let a: Chromosome = vec![0.0, 0.0].into_iter().collect();
+        let b: Chromosome = vec![3.0, 4.0].into_iter().collect();
+        assert_relative_eq!(EuclideanDistance.distance(&a, &b), 5.0);
+
+        let a: Chromosome<bool> = vec![true, false, true].into_iter().collect();
+        let b: Chromosome<bool> = vec![true, true, false].into_iter().collect();
+        assert_relative_eq!(HammingDistance.distance(&a, &b), 2.0);
+
+        let manhattan = |a: &Chromosome, b: &Chromosome| a.iter().zip(b.iter()).map(|(a, b)| (a - b).abs()).sum();
+        let a: Chromosome = vec![0.0, 0.0].into_iter().collect();
+        let b: Chromosome = vec![3.0, 4.0].into_iter().collect();
+        assert_relative_eq!(manhattan.distance(&a, &b), 7.0);
+    }
+
+    #[test]
+    fn speciate_and_share() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut speciation = speciation();
+        let population = vec![point(1.0, 0.0), point(1.5, 0.0), point(10.0, 10.0), point(2.0, 0.0)];
+
+        speciation.speciate(&mut rng, &population);
+        let members: Vec<&[usize]> = speciation.species().iter().map(Species::members).collect();
+        assert_eq!(members, vec![&[0, 1, 3][..], &[2][..]]);
+
+        let shared = speciation.shared_fitness(&population);
+        assert_relative_eq!(shared.as_slice(), [1.0 / 3.0, 0.5, 10.0, 2.0 / 3.0].as_ref());
+    }
+
+    #[test]
+    fn allocation() {
+        assert_eq!(allocate(&[1.0, 1.0, 2.0], 8), vec![2, 2, 4]);
+        assert_eq!(allocate(&[1.0, 1.0, 1.0], 10).iter().sum::<usize>(), 10);
+        assert_eq!(allocate(&[0.0, 0.0], 5).iter().sum::<usize>(), 5);
+        assert_eq!(allocate(&[3.0, 0.0], 5), vec![5, 0]);
+    }
+
+    #[test]
+    fn stagnant_species_die_out() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut speciation = speciation().with_stagnation_limit(2);
+
+        // the left cluster never improves and is always worse than the right one
+        let mut population = vec![point(8.0, 0.0), point(8.5, 0.0), point(10.0, 0.0), point(10.5, 0.0)];
+        let next = speciation.evolve(&mut rng, &population);
+        assert_eq!(next.iter().filter(|individual| individual.fitness() < 10.0).count(), 2);
+
+        for _ in 0..3 {
+            population = speciation.evolve(&mut rng, &population);
+            assert_eq!(population.len(), 4);
+        }
+        assert!(population.iter().all(|individual| individual.fitness() >= 10.0));
+    }
+
+    #[test]
+    fn protects_small_species() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut speciation = speciation();
+
+        // one lone newcomer among many strong individuals still gets offspring of its own
+        let mut population: Vec<Point> = (0..9).map(|n| point(5.0 + n as f32 * 0.01, 0.0)).collect();
+        population.push(point(3.0, 20.0));
+
+        let next = speciation.evolve(&mut rng, &population);
+        assert_eq!(next.len(), 10);
+        assert!(next.iter().any(|individual| individual.0[1] == 20.0));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::*;
+
+// Genes that can be read as a number, so diversity can be measured; `bool` counts as 0 or 1
+pub trait NumericGene {
+    fn value(&self) -> f32;
+}
+
+macro_rules! numeric_gene {
+    ($($gene:ty),*) => {
+        $(
+            impl NumericGene for $gene {
+                fn value(&self) -> f32 {
+                    *self as f32
+                }
+            }
+        )*
+    };
+}
+
+numeric_gene!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl NumericGene for bool {
+    fn value(&self) -> f32 {
+        if *self { 1.0 } else { 0.0 }
+    }
+}
+
+const ENTROPY_BINS: usize = 10;
+
+// Fitness spread and gene-level diversity of one evaluated population.
+// Not part of `GenericAlgorithm::evolve`'s result, since it needs `NumericGene` genes and O(N² · L) work:
+// get it from `GenericAlgorithm::evolve_with_statistics`, or call `Statistics::new` on any population.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    sorted_fitness: Vec<f32>,
+    mean: f32,
+    std_dev: f32,
+    mean_distance: f32,     // average Euclidean distance between two chromosomes
+    gene_variance: Vec<f32>, // per position
+    gene_entropy: Vec<f32>,  // per position, in bits over ENTROPY_BINS equal-width bins
+}
+
+impl Statistics {
+    pub fn new<I>(population: &[I]) -> Self
+    where
+        I: Individual,
+        I::Gene: NumericGene,
+    {
+        assert!(!population.is_empty());
+        let size = population.len() as f32;
+
+        let mut sorted_fitness: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        sorted_fitness.sort_by(f32::total_cmp);
+        let mean = sorted_fitness.iter().sum::<f32>() / size;
+        let std_dev = (sorted_fitness.iter().map(|fitness| (fitness - mean).powi(2)).sum::<f32>() / size).sqrt();
+
+        let genes: Vec<Vec<f32>> = population
+            .iter()
+            .map(|individual| individual.chromosome().iter().map(NumericGene::value).collect())
+            .collect();
+        let length = genes[0].len();
+        assert!(genes.iter().all(|chromosome| chromosome.len() == length), "chromosomes should have the same length");
+
+        let mut total_distance = 0.0;
+        for (index, a) in genes.iter().enumerate() {
+            for b in &genes[index + 1..] {
+                total_distance += a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt();
+            }
+        }
+        let pairs = population.len() * (population.len() - 1) / 2;
+        let mean_distance = if pairs > 0 { total_distance / pairs as f32 } else { 0.0 };
+
+        let columns: Vec<Vec<f32>> = (0..length)
+            .map(|position| genes.iter().map(|chromosome| chromosome[position]).collect())
+            .collect();
+        let gene_variance = columns.iter().map(|column| variance(column)).collect();
+        let gene_entropy = columns.iter().map(|column| entropy(column)).collect();
+
+        Self { sorted_fitness, mean, std_dev, mean_distance, gene_variance, gene_entropy }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sorted_fitness.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorted_fitness.is_empty()
+    }
+
+    pub fn min(&self) -> f32 {
+        self.sorted_fitness[0]
+    }
+
+    pub fn max(&self) -> f32 {
+        self.sorted_fitness[self.sorted_fitness.len() - 1]
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    pub fn median(&self) -> f32 {
+        self.quantile(0.5)
+    }
+
+    pub fn std_dev(&self) -> f32 {
+        self.std_dev
+    }
+
+    // Fitness below which a `q` share of the population lies, interpolating between neighbours
+    pub fn quantile(&self, q: f32) -> f32 {
+        assert!((0.0..=1.0).contains(&q));
+        let position = q * (self.sorted_fitness.len() - 1) as f32;
+        let (below, above) = (position.floor() as usize, position.ceil() as usize);
+        let (low, high) = (self.sorted_fitness[below], self.sorted_fitness[above]);
+        low + (high - low) * (position - below as f32)
+    }
+
+    pub fn mean_distance(&self) -> f32 {
+        self.mean_distance
+    }
+
+    pub fn gene_variance(&self) -> &[f32] {
+        &self.gene_variance
+    }
+
+    pub fn gene_entropy(&self) -> &[f32] {
+        &self.gene_entropy
+    }
+
+    // Averaged over positions; 0.0 once every chromosome is the same
+    pub fn mean_gene_variance(&self) -> f32 {
+        average(&self.gene_variance)
+    }
+
+    pub fn mean_gene_entropy(&self) -> f32 {
+        average(&self.gene_entropy)
+    }
+}
+
+fn average(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn variance(values: &[f32]) -> f32 {
+    let mean = average(values);
+    average(&values.iter().map(|value| (value - mean).powi(2)).collect::<Vec<f32>>())
+}
+
+fn entropy(values: &[f32]) -> f32 {
+    let low = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let high = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if high <= low {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; ENTROPY_BINS];
+    for value in values {
+        let bin = ((value - low) / (high - low) * ENTROPY_BINS as f32) as usize;
+        counts[bin.min(ENTROPY_BINS - 1)] += 1;
+    }
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f32 / values.len() as f32;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    struct Sample {
+        fitness: f32,
+        chromosome: Chromosome<i32>,
+    }
+
+    impl Individual for Sample {
+        type Gene = i32;
+
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+
+        fn chromosome(&self) -> &Chromosome<i32> {
+            &self.chromosome
+        }
+
+        fn create(chromosome: Chromosome<i32>) -> Self {
+            Self { fitness: 0.0, chromosome }
+        }
+    }
+
+    fn sample(fitness: f32, genes: Vec<i32>) -> Sample {
+        Sample { fitness, chromosome: genes.into_iter().collect() }
+    }
+
+    #[test]
+    fn fitness_statistics() {
+        let population = vec![
+            sample(4.0, vec![0, 0]),
+            sample(1.0, vec![0, 0]),
+            sample(3.0, vec![0, 0]),
+            sample(2.0, vec![0, 0]),
+        ];
+        let statistics = Statistics::new(&population);
+
+        assert_eq!(statistics.len(), 4);
+        assert_relative_eq!(statistics.min(), 1.0);
+        assert_relative_eq!(statistics.max(), 4.0);
+        assert_relative_eq!(statistics.mean(), 2.5);
+        assert_relative_eq!(statistics.median(), 2.5);
+        assert_relative_eq!(statistics.std_dev(), 1.25f32.sqrt());
+        assert_relative_eq!(statistics.quantile(0.0), 1.0);
+        assert_relative_eq!(statistics.quantile(0.25), 1.75);
+        assert_relative_eq!(statistics.quantile(1.0), 4.0);
+    }
+
+    #[test]
+    fn diversity() {
+        let population = vec![
+            sample(0.0, vec![0, 0, 5]),
+            sample(0.0, vec![3, 0, 5]),
+            sample(0.0, vec![0, 4, 5]),
+            sample(0.0, vec![3, 4, 5]),
+        ];
+        let statistics = Statistics::new(&population);
+
+        // pairs: 3, 4, 5, 5, 4, 3
+        assert_relative_eq!(statistics.mean_distance(), 4.0);
+        assert_relative_eq!(statistics.gene_variance(), [2.25, 4.0, 0.0].as_ref());
+        // two equally likely values: one bit; a constant gene: none
+        assert_relative_eq!(statistics.gene_entropy(), [1.0, 1.0, 0.0].as_ref());
+        assert_relative_eq!(statistics.mean_gene_entropy(), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn converged_population() {
+        let population = vec![sample(1.0, vec![7, 7]), sample(1.0, vec![7, 7])];
+        let statistics = Statistics::new(&population);
+        assert_eq!(statistics.mean_distance(), 0.0);
+        assert_eq!(statistics.mean_gene_variance(), 0.0);
+        assert_eq!(statistics.mean_gene_entropy(), 0.0);
+        assert_eq!(statistics.std_dev(), 0.0);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Activation {
+    #[default]
+    Relu,
+    LeakyRelu { alpha: f32 },
+    Sigmoid,
+    Tanh,
+    Identity,
+    Softsign,
+    Step,
+}
+
+impl Activation {
+    pub fn apply(&self, value: f32) -> f32 {
+        match *self {
+            Self::Relu => value.max(0.0),
+            Self::LeakyRelu { alpha } => if value > 0.0 { value } else { alpha * value },
+            Self::Sigmoid => 1.0 / (1.0 + (-value).exp()),
+            Self::Tanh => value.tanh(),
+            Self::Identity => value,
+            Self::Softsign => value / (1.0 + value.abs()),
+            Self::Step => if value > 0.0 { 1.0 } else { 0.0 },
+        }
+    }
+
+    // d apply / d value; step is treated as flat everywhere
+    pub fn derivative(&self, value: f32) -> f32 {
+        match *self {
+            Self::Relu => if value > 0.0 { 1.0 } else { 0.0 },
+            Self::LeakyRelu { alpha } => if value > 0.0 { 1.0 } else { alpha },
+            Self::Sigmoid => {
+                let sigmoid = self.apply(value);
+                sigmoid * (1.0 - sigmoid)
+            }
+            Self::Tanh => 1.0 - value.tanh().powi(2),
+            Self::Identity => 1.0,
+            Self::Softsign => 1.0 / (1.0 + value.abs()).powi(2),
+            Self::Step => 0.0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn check(activation: Activation, expected: [f32; 5]) {
+        let actual: Vec<f32> = [-2.0, -0.5, 0.0, 0.5, 2.0]
+            .iter()
+            .map(|&value| activation.apply(value))
+            .collect();
+        assert_relative_eq!(actual.as_slice(), expected.as_ref());
+    }
+
+    #[test]
+    fn derivative_matches_finite_difference() {
+        let activations = [
+            Activation::LeakyRelu { alpha: 0.1 },
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Identity,
+            Activation::Softsign,
+        ];
+        for activation in activations {
+            for value in [-2.0, -0.5, 0.5, 2.0] {
+                let h = 1e-3;
+                let expected = (activation.apply(value + h) - activation.apply(value - h)) / (2.0 * h);
+                assert_relative_eq!(activation.derivative(value), expected, epsilon = 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn relu() {
+        check(Activation::Relu, [0.0, 0.0, 0.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn leaky_relu() {
+        check(Activation::LeakyRelu { alpha: 0.1 }, [-0.2, -0.05, 0.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn sigmoid() {
+        check(Activation::Sigmoid, [0.11920292, 0.37754068, 0.5, 0.62245935, 0.8807971]);
+    }
+
+    #[test]
+    fn tanh() {
+        check(Activation::Tanh, [-0.9640276, -0.46211717, 0.0, 0.46211717, 0.9640276]);
+    }
+
+    #[test]
+    fn identity() {
+        check(Activation::Identity, [-2.0, -0.5, 0.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn softsign() {
+        check(Activation::Softsign, [-0.6666667, -0.33333334, 0.0, 0.33333334, 0.6666667]);
+    }
+
+    #[test]
+    fn step() {
+        check(Activation::Step, [0.0, 0.0, 0.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt::Write;
+
+use crate::*;
+
+const POSITIVE: (u8, u8, u8) = (0x21, 0x66, 0xac);
+const NEGATIVE: (u8, u8, u8) = (0xb2, 0x18, 0x2b);
+const NEUTRAL: (u8, u8, u8) = (0xff, 0xff, 0xff);
+
+const MIN_PEN: f32 = 0.5;
+const MAX_PEN: f32 = 4.0;
+
+const SVG_LAYER_GAP: f32 = 160.0;
+const SVG_NEURON_GAP: f32 = 40.0;
+const SVG_MARGIN: f32 = 40.0;
+const SVG_RADIUS: f32 = 12.0;
+
+// Edge thickness follows |weight| relative to the heaviest edge, colour follows the sign.
+// Nodes are shaded from a `propagate_traced` snapshot when one is given; gated layers only show their candidate weights.
+impl Network {
+    pub fn to_dot(&self, trace: Option<&Trace>) -> String {
+        let max_weight = self.max_weight();
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph network {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    splines=line;").unwrap();
+        writeln!(dot, "    node [shape=circle, style=filled, label=\"\"];").unwrap();
+
+        for (layer, size) in self.layer_sizes().enumerate() {
+            writeln!(dot, "    subgraph cluster_{} {{", layer).unwrap();
+            writeln!(dot, "        color=none;").unwrap();
+            for neuron in 0..size {
+                let fill = node_value(trace, layer, neuron).map_or(NEUTRAL, value_color);
+                writeln!(dot, "        l{}_n{} [fillcolor=\"{}\"];", layer, neuron, hex(fill)).unwrap();
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        self.for_each_edge(|edge| {
+            let style = if edge.recurrent { ", style=dashed, constraint=false" } else { "" };
+            writeln!(
+                dot,
+                "    l{}_n{} -> l{}_n{} [color=\"{}\", penwidth={:.2}{}];",
+                edge.from.0, edge.from.1, edge.to.0, edge.to.1,
+                hex(weight_color(edge.weight)), pen_width(edge.weight, max_weight), style,
+            )
+            .unwrap();
+        });
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    pub fn to_svg(&self, trace: Option<&Trace>) -> String {
+        let max_weight = self.max_weight();
+        let sizes: Vec<usize> = self.layer_sizes().collect();
+        let tallest = sizes.iter().copied().max().unwrap_or(0) as f32;
+
+        let width = 2.0 * SVG_MARGIN + (sizes.len() - 1) as f32 * SVG_LAYER_GAP;
+        let height = 2.0 * SVG_MARGIN + (tallest - 1.0).max(0.0) * SVG_NEURON_GAP;
+        let position = |layer: usize, neuron: usize| {
+            let offset = (tallest - sizes[layer] as f32) * SVG_NEURON_GAP / 2.0;
+            (
+                SVG_MARGIN + layer as f32 * SVG_LAYER_GAP,
+                SVG_MARGIN + offset + neuron as f32 * SVG_NEURON_GAP,
+            )
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width, h = height,
+        )
+        .unwrap();
+        writeln!(svg, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+
+        // edges first so the neurons are drawn on top of them
+        self.for_each_edge(|edge| {
+            let (x1, y1) = position(edge.from.0, edge.from.1);
+            let (x2, y2) = position(edge.to.0, edge.to.1);
+            let dash = if edge.recurrent { " stroke-dasharray=\"4 3\"" } else { "" };
+            let stroke = format!(
+                "stroke=\"{}\" stroke-width=\"{:.2}\" stroke-opacity=\"0.8\"{}",
+                hex(weight_color(edge.weight)), pen_width(edge.weight, max_weight), dash,
+            );
+
+            if edge.from == edge.to {
+                // a neuron feeding itself: a small loop above it, short enough to stay clear of the neuron above
+                let (dx, dy) = (0.7 * SVG_RADIUS, 0.7 * SVG_RADIUS);
+                let (reach, lift) = (1.6 * SVG_RADIUS, 2.4 * SVG_RADIUS);
+                writeln!(
+                    svg,
+                    "  <path d=\"M {:.1} {:.1} C {:.1} {:.1} {:.1} {:.1} {:.1} {:.1}\" fill=\"none\" {}/>",
+                    x1 + dx, y1 - dy, x1 + reach, y1 - lift, x1 - reach, y1 - lift, x1 - dx, y1 - dy, stroke,
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    svg,
+                    "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {}/>",
+                    x1, y1, x2, y2, stroke,
+                )
+                .unwrap();
+            }
+        });
+
+        for (layer, &size) in sizes.iter().enumerate() {
+            for neuron in 0..size {
+                let (x, y) = position(layer, neuron);
+                let fill = node_value(trace, layer, neuron).map_or(NEUTRAL, value_color);
+                writeln!(
+                    svg,
+                    "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"black\"/>",
+                    x, y, SVG_RADIUS, hex(fill),
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+
+    // input layer first, then every layer's output width
+    fn layer_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.input_size()).chain(self.layers.iter().map(|layer| layer.output_size))
+    }
+
+    fn max_weight(&self) -> f32 {
+        let mut max_weight: f32 = 0.0;
+        self.for_each_edge(|edge| max_weight = max_weight.max(edge.weight.abs()));
+        max_weight
+    }
+
+    fn for_each_edge(&self, mut f: impl FnMut(Edge)) {
+        for (index, layer) in self.layers.iter().enumerate() {
+            let (source, target) = (index, index + 1);
+            for (neuron, row) in layer.neurons().take(layer.output_size).enumerate() {
+                for (input, &weight) in row.weights().iter().enumerate() {
+                    f(Edge { from: (source, input), to: (target, neuron), weight, recurrent: false });
+                }
+                for (hidden, &weight) in row.recurrent_weights().iter().enumerate() {
+                    f(Edge { from: (target, hidden), to: (target, neuron), weight, recurrent: true });
+                }
+            }
+        }
+    }
+}
+
+struct Edge {
+    from: (usize, usize), // (layer, neuron), layer 0 being the input
+    to: (usize, usize),
+    weight: f32,
+    recurrent: bool,
+}
+
+fn node_value(trace: Option<&Trace>, layer: usize, neuron: usize) -> Option<f32> {
+    let trace = trace?;
+    let values = match layer {
+        0 => &trace.input,
+        _ => &trace.layers.get(layer - 1)?.post_activation,
+    };
+    values.get(neuron).copied()
+}
+
+fn pen_width(weight: f32, max_weight: f32) -> f32 {
+    if max_weight == 0.0 {
+        return MIN_PEN;
+    }
+    MIN_PEN + (MAX_PEN - MIN_PEN) * weight.abs() / max_weight
+}
+
+fn weight_color(weight: f32) -> (u8, u8, u8) {
+    if weight < 0.0 { NEGATIVE } else { POSITIVE }
+}
+
+// white at 0, fading towards the sign colour as the value approaches ±1
+fn value_color(value: f32) -> (u8, u8, u8) {
+    let target = weight_color(value);
+    let t = value.abs().min(1.0);
+    let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+    (mix(NEUTRAL.0, target.0), mix(NEUTRAL.1, target.1), mix(NEUTRAL.2, target.2))
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network() -> Network {
+        let layers = vec![
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 1, activation: Activation::Tanh, kind: LayerKind::Elman},
+        ];
+        Network::from_params(&layers, vec![0.1, 2.0, -1.0, 0.5])
+    }
+
+    #[test]
+    fn to_dot() {
+        let dot = network().to_dot(None);
+        assert!(dot.starts_with("digraph network {"));
+        assert!(dot.contains("l0_n0 -> l1_n0 [color=\"#2166ac\", penwidth=4.00];"));
+        assert!(dot.contains("l0_n1 -> l1_n0 [color=\"#b2182b\", penwidth=2.25];"));
+        assert!(dot.contains("l1_n0 -> l1_n0 [color=\"#2166ac\", penwidth=1.38, style=dashed, constraint=false];"));
+        assert!(dot.contains("l1_n0 [fillcolor=\"#ffffff\"];"));
+    }
+
+    #[test]
+    fn to_dot_with_trace() {
+        let network = network();
+        let trace = network.propagate_traced(&[-1.0, 0.0]);
+        let dot = network.to_dot(Some(&trace));
+        assert!(dot.contains("l0_n0 [fillcolor=\"#b2182b\"];"));
+        assert!(dot.contains("l0_n1 [fillcolor=\"#ffffff\"];"));
+    }
+
+    #[test]
+    fn to_svg() {
+        let svg = network().to_svg(None);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 2);
+        // the Elman self-connection is a loop, not a zero-length line
+        assert!(svg.contains("<path d=\"M 208.4 51.6 C 219.2 31.2 180.8 31.2 191.6 51.6\" fill=\"none\""));
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1);
+    }
+}
@@ -0,0 +1,26 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkError {
+    EmptyTopology, // fewer than two layers
+    EmptyLayer { layer: usize },
+    ParamCount { expected: usize, actual: usize },
+    InputWidth { expected: usize, actual: usize },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyTopology => write!(f, "topology needs at least two layers"),
+            Self::EmptyLayer { layer } => write!(f, "layer {} has no neurons", layer),
+            Self::ParamCount { expected, actual } => {
+                write!(f, "expected {} params, got {}", expected, actual)
+            }
+            Self::InputWidth { expected, actual } => {
+                write!(f, "expected {} inputs, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
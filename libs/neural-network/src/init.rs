@@ -0,0 +1,119 @@
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WeightInit {
+    Uniform { low: f32, high: f32 },
+    Normal { sigma: f32 },
+    Xavier, // uniform in ±sqrt(6 / (fan_in + fan_out)), suits tanh and sigmoid
+    He,     // normal with sigma sqrt(2 / fan_in), suits relu
+}
+
+impl Default for WeightInit {
+    fn default() -> Self {
+        Self::Uniform { low: -1.0, high: 1.0 }
+    }
+}
+
+impl WeightInit {
+    // Panics on parameters that cannot be sampled, so a bad config fails when the initializer is built
+    pub fn validate(&self) {
+        match *self {
+            Self::Uniform { low, high } => assert!(
+                low.is_finite() && high.is_finite() && low <= high,
+                "uniform weight range should be finite with low <= high, got {}..={}", low, high
+            ),
+            Self::Normal { sigma } => assert!(
+                sigma.is_finite() && sigma >= 0.0,
+                "normal weight sigma should be finite and non-negative, got {}", sigma
+            ),
+            Self::Xavier | Self::He => {}
+        }
+    }
+
+    pub fn sample(&self, rng: &mut dyn RngCore, fan_in: usize, fan_out: usize) -> f32 {
+        match *self {
+            Self::Uniform { low, high } => rng.gen_range(low..=high),
+            Self::Normal { sigma } => sigma * sample_normal(rng),
+            Self::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out) as f32).sqrt();
+                rng.gen_range(-limit..=limit)
+            }
+            Self::He => (2.0 / fan_in.max(1) as f32).sqrt() * sample_normal(rng),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Initializer {
+    pub weights: WeightInit,
+    pub zero_bias: bool, // otherwise biases are drawn like weights
+}
+
+impl Initializer {
+    pub fn new(weights: WeightInit) -> Self {
+        weights.validate();
+        Self { weights, zero_bias: false }
+    }
+
+    pub fn with_zero_bias(mut self) -> Self {
+        self.zero_bias = true;
+        self
+    }
+
+    pub(crate) fn bias(&self, rng: &mut dyn RngCore, fan_in: usize, fan_out: usize) -> f32 {
+        if self.zero_bias {
+            0.0
+        } else {
+            self.weights.sample(rng, fan_in, fan_out)
+        }
+    }
+}
+
+fn sample_normal(rng: &mut dyn RngCore) -> f32 {
+    StandardNormal.sample(rng)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn samples(init: WeightInit, fan_in: usize, fan_out: usize) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        (0..10_000).map(|_| init.sample(&mut rng, fan_in, fan_out)).collect()
+    }
+
+    fn std_dev(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+        variance.sqrt()
+    }
+
+    #[test]
+    fn uniform_stays_in_range() {
+        let values = samples(WeightInit::Uniform { low: 0.5, high: 0.75 }, 4, 4);
+        assert!(values.iter().all(|&v| (0.5..=0.75).contains(&v)));
+    }
+
+    #[test]
+    #[should_panic(expected = "low <= high")]
+    fn inverted_uniform_range() {
+        Initializer::new(WeightInit::Uniform { low: 1.0, high: -1.0 });
+    }
+
+    #[test]
+    fn xavier_shrinks_with_fan() {
+        let values = samples(WeightInit::Xavier, 20, 4);
+        assert!(values.iter().all(|&v| v.abs() <= 0.5));
+    }
+
+    #[test]
+    fn normal_and_he_have_expected_spread() {
+        assert!((std_dev(&samples(WeightInit::Normal { sigma: 0.3 }, 4, 4)) - 0.3).abs() < 0.01);
+        assert!((std_dev(&samples(WeightInit::He, 8, 4)) - 0.5).abs() < 0.02);
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayerKind {
+    #[default]
+    Dense,
+    // h = act(b + W x + U h_prev)
+    Elman,
+    // z = sigmoid(bz + Wz x + Uz h_prev), h = h_prev + z * (act(b + W x + U h_prev) - h_prev)
+    Gated,
+}
+
+impl LayerKind {
+    pub fn is_recurrent(&self) -> bool {
+        *self != Self::Dense
+    }
+
+    // rows per neuron: the gated unit carries an extra row for its update gate
+    pub(crate) fn gates(&self) -> usize {
+        match self {
+            Self::Dense | Self::Elman => 1,
+            Self::Gated => 2,
+        }
+    }
+
+    // Where row `row` is stored: gated layers keep each neuron's candidate row and gate row side by side,
+    // while rows are still numbered every candidate first, then the matching update gates
+    pub(crate) fn row_position(&self, row: usize, output_size: usize) -> usize {
+        match self {
+            Self::Dense | Self::Elman => row,
+            Self::Gated if row < output_size => 2 * row,
+            Self::Gated => 2 * (row - output_size) + 1,
+        }
+    }
+
+    pub(crate) fn row_size(&self, input_size: usize, output_size: usize) -> usize {
+        let recurrent_size = if self.is_recurrent() { output_size } else { 0 };
+        1 + input_size + recurrent_size
+    }
+
+    pub fn param_count(&self, input_size: usize, output_size: usize) -> usize {
+        self.gates() * output_size * self.row_size(input_size, output_size)
+    }
+}
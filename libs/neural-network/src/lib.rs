@@ -1,10 +1,37 @@
-use std::iter::once;
-
-use rand::{Rng, RngCore};
-
-#[derive(Debug)]
+mod activation;
+mod diagram;
+mod error;
+mod init;
+mod layer_kind;
+mod neat;
+mod persist;
+mod scratch;
+mod state;
+mod train;
+mod trace;
+
+pub use activation::*;
+pub use error::*;
+pub use init::*;
+pub use layer_kind::*;
+pub use neat::*;
+pub use persist::*;
+pub use scratch::*;
+pub use state::*;
+pub use train::*;
+pub use trace::*;
+
+use std::ops::Range;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct LayerTopology {
     pub num_neuron: usize,
+    pub activation: Activation, // ignored for the input layer
+    #[serde(default)]
+    pub kind: LayerKind, // ignored for the input layer
 }
 
 #[derive(Debug, Clone)]
@@ -14,10 +41,21 @@ pub struct Network {
 impl Network {
 
     pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
+        Self::random_with(rng, layers, Initializer::default())
+    }
+
+    pub fn random_with(rng: &mut dyn RngCore, layers: &[LayerTopology], init: Initializer) -> Self {
         assert!(layers.len() > 1);
         let layers = layers
                                 .windows(2)
-                                .map(|adjacent_layers| Layer::random(rng, adjacent_layers[0].num_neuron, adjacent_layers[1].num_neuron))
+                                .map(|adjacent_layers| Layer::random(
+                                    rng,
+                                    adjacent_layers[0].num_neuron,
+                                    adjacent_layers[1].num_neuron,
+                                    adjacent_layers[1].kind,
+                                    adjacent_layers[1].activation,
+                                    init,
+                                ))
                                 .collect();
         
         Self { layers }
@@ -29,10 +67,30 @@ impl Network {
             .fold(inputs, |inputs, layer| layer.propagate(inputs))
     }
 
+    pub fn try_propagate(&self, inputs: Vec<f32>) -> Result<Vec<f32>, NetworkError> {
+        let expected = self.input_size();
+        if inputs.len() != expected {
+            return Err(NetworkError::InputWidth { expected, actual: inputs.len() });
+        }
+
+        Ok(self.propagate(inputs))
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].input_size
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].output_size
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
     pub fn get_params(&self) -> Vec<f32> {
         self.layers.iter()
-                    .flat_map(|layer| layer.neurons.iter())
-                    .flat_map(|neuron| once(&neuron.bias).chain(&neuron.weights))
+                    .flat_map(|layer| layer.params.iter())
                     .copied()
                     .collect()
     }
@@ -41,7 +99,25 @@ impl Network {
         layers: &[LayerTopology],
         params: impl IntoIterator<Item = f32>,
     ) -> Self {
-        assert!(layers.len() > 1);
+        Self::try_from_params(layers, params).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_from_params(
+        layers: &[LayerTopology],
+        params: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, NetworkError> {
+        if layers.len() < 2 {
+            return Err(NetworkError::EmptyTopology);
+        }
+        if let Some(layer) = layers.iter().position(|layer| layer.num_neuron == 0) {
+            return Err(NetworkError::EmptyLayer { layer });
+        }
+
+        let params: Vec<f32> = params.into_iter().collect();
+        let expected = Self::param_count(layers);
+        if params.len() != expected {
+            return Err(NetworkError::ParamCount { expected, actual: params.len() });
+        }
 
         let mut params = params.into_iter();
 
@@ -51,96 +127,137 @@ impl Network {
                     Layer::from_params(
                         adjacent_layers[0].num_neuron, 
                         adjacent_layers[1].num_neuron, 
+                        adjacent_layers[1].kind,
+                        adjacent_layers[1].activation,
                         &mut params,
                     )
                 })
             .collect();
         
-        if params.next().is_some() {
-            panic!("too much weights")
-        }
-        
-        Self { layers }
+        Ok(Self { layers })
+    }
+
+    pub fn param_count(layers: &[LayerTopology]) -> usize {
+        layers
+            .windows(2)
+            .map(|adjacent_layers| {
+                adjacent_layers[1].kind.param_count(adjacent_layers[0].num_neuron, adjacent_layers[1].num_neuron)
+            })
+            .sum()
+    }
+
+    // Length of every neuron's parameters in `get_params` order, e.g. for neuron-aligned crossover:
+    // its [bias, weights..] row, plus the update-gate row right after it for gated layers
+    pub fn neuron_blocks(layers: &[LayerTopology]) -> Vec<usize> {
+        layers
+            .windows(2)
+            .flat_map(|adjacent_layers| {
+                let (input_size, output_size) = (adjacent_layers[0].num_neuron, adjacent_layers[1].num_neuron);
+                let kind = adjacent_layers[1].kind;
+                std::iter::repeat_n(kind.gates() * kind.row_size(input_size, output_size), output_size)
+            })
+            .collect()
     }
 }
 
 #[derive(Debug, Clone)]
-struct Layer {
-    neurons: Vec<Neuron>,
+pub struct Layer {
+    input_size: usize,
+    output_size: usize,
+    kind: LayerKind,
+    params: Vec<f32>, // one row per neuron and gate: [bias, input weights.., recurrent weights..]
+    activation: Activation,
 }
 impl Layer {
     fn from_params(
         input_size: usize, 
         output_size: usize, 
+        kind: LayerKind,
+        activation: Activation,
         params: &mut dyn Iterator<Item = f32>
     ) -> Self {
-        let neurons = (0..output_size)
-            .map(|_| Neuron::from_params(input_size, params))
-            .collect();
+        let param_count = kind.param_count(input_size, output_size);
+        let params = params
+            .take(param_count)
+            .collect::<Vec<_>>();
+        assert_eq!(params.len(), param_count, "got not enough weights");
         
-        Self { neurons }
+        Self { input_size, output_size, kind, params, activation }
     }
 
-    fn random(rng: &mut dyn RngCore, input_size:usize, output_size:usize) -> Self {
-        let neurons = (0..output_size)
-            .map(|_| Neuron::random(rng, input_size))
-            .collect();
+    fn random(
+        rng: &mut dyn RngCore,
+        input_size:usize,
+        output_size:usize,
+        kind: LayerKind,
+        activation: Activation,
+        init: Initializer,
+    ) -> Self {
+        let row_size = kind.row_size(input_size, output_size);
+        let fan_in = row_size - 1;
+        let mut params = Vec::with_capacity(kind.param_count(input_size, output_size));
+        for _ in 0..output_size * kind.gates() {
+            // weights are drawn before the bias, so seeded brains stay the same as before
+            let weights = (1..row_size)
+                                .map(|_| init.weights.sample(rng, fan_in, output_size))
+                                .collect::<Vec<f32>>();
+            params.push(init.bias(rng, fan_in, output_size));
+            params.extend(weights);
+        }
         
-        Self { neurons }
+        Self { input_size, output_size, kind, params, activation }
     }
 
-    fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.neurons
-            .iter()
-            .map(|neuron| neuron.propagate(&inputs))
-            .collect()
+    fn row(&self, index: usize) -> &[f32] {
+        &self.params[self.row_range(index)]
     }
-}
 
-// trait Activation {
-//     fn activation(value: f32) -> f32;
-// }
-
-#[derive(PartialEq)]
-#[derive(Debug, Clone)]
-struct Neuron {
-    weights: Vec<f32>,
-    bias: f32,
-}
-
-impl Neuron { // random::<f32>()
-
-    fn from_params(
-        input_size: usize, 
-        params: &mut dyn Iterator<Item = f32>
-    ) -> Self {
-        let bias = params.next().expect("got not enough weights");
-        let weights = (0..input_size)
-                                .map(|_| params.next().expect("got not enough weights"))
-                                .collect();
-        Self { weights, bias }
+    pub(crate) fn row_range(&self, index: usize) -> Range<usize> {
+        let row_size = self.kind.row_size(self.input_size, self.output_size);
+        let position = self.kind.row_position(index, self.output_size);
+        position * row_size..(position + 1) * row_size
     }
 
-    fn random(rng: &mut dyn RngCore, input_size:usize) -> Self {
-        // let mut rng = rand::thread_rng();
-        let weights = (0..input_size)
-                                .map(|_| rng.gen_range(-1.0..=1.0))
-                                .collect();
-        let bias = rng.gen_range(-1.0..=1.0);
+    // bias + W x (+ U h_prev for recurrent layers, where a missing state counts as all zeros)
+    fn weighted_sum(&self, index: usize, inputs: &[f32], hidden: Option<&[f32]>) -> f32 {
+        let (bias, weights) = self.row(index).split_first().unwrap();
+        let (input_weights, hidden_weights) = weights.split_at(self.input_size);
+
+        let dot = |values: &[f32], weights: &[f32]| {
+            values
+                .iter()
+                .zip(weights)
+                .map(|(value, weight)| value * weight)
+                .sum::<f32>()
+        };
 
-        Self { weights, bias}
+        bias + dot(inputs, input_weights) + hidden.map_or(0.0, |hidden| dot(hidden, hidden_weights))
     }
 
-    fn propagate(&self, inputs: &[f32]) -> f32 {
-        assert_eq!(inputs.len(), self.weights.len());
-        let output = inputs
-                        .iter()
-                        .zip(self.weights.iter())
-                        .map(|(input, weight)| input * weight)
-                        .sum::<f32>();
-        
-        (output + self.bias).max(0.0)
+    fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        let mut outputs = vec![0.0; self.output_size];
+        self.propagate_into(&inputs, None, &mut outputs);
+        outputs
+    }
 
+    fn propagate_into(&self, inputs: &[f32], hidden: Option<&[f32]>, outputs: &mut [f32]) {
+        assert_eq!(inputs.len(), self.input_size);
+        assert_eq!(outputs.len(), self.output_size);
+
+        for (index, output) in outputs.iter_mut().enumerate() {
+            let candidate = self.activation.apply(self.weighted_sum(index, inputs, hidden));
+
+            *output = match self.kind {
+                LayerKind::Dense | LayerKind::Elman => candidate,
+                LayerKind::Gated => {
+                    let gate = Activation::Sigmoid.apply(
+                        self.weighted_sum(self.output_size + index, inputs, hidden)
+                    );
+                    let previous = hidden.map_or(0.0, |hidden| hidden[index]);
+                    previous + gate * (candidate - previous)
+                }
+            };
+        }
     }
 }
 
@@ -156,8 +273,8 @@ mod test {
     #[test]
     fn from_params() {
         let layers = vec![
-            LayerTopology {num_neuron: 3},
-            LayerTopology {num_neuron: 2},
+            LayerTopology {num_neuron: 3, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
         ];
 
         let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
@@ -168,51 +285,71 @@ mod test {
         assert_relative_eq!(actual.as_slice(), expected.as_slice());
     }
     
+    // one (bias, weights) pair per neuron
+    fn layer(neurons: &[(f32, &[f32])], activation: Activation) -> Layer {
+        Layer {
+            input_size: neurons[0].1.len(),
+            output_size: neurons.len(),
+            kind: LayerKind::Dense,
+            params: neurons
+                .iter()
+                .flat_map(|(bias, weights)| std::iter::once(bias).chain(weights.iter()))
+                .copied()
+                .collect(),
+            activation,
+        }
+    }
+
     #[test]
     fn random() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
-        let neuron = Neuron::random(&mut rng, 4);
-        assert_relative_eq!(neuron.bias, 0.5238807);
+        let layer = Layer::random(&mut rng, 4, 1, LayerKind::Dense, Activation::Relu, Initializer::default());
         assert_relative_eq!(
-            neuron.weights.as_slice(), 
-            [-0.6255188, 0.67383957, 0.8181262, 0.26284897].as_ref()
+            layer.params.as_slice(), 
+            [0.5238807, -0.6255188, 0.67383957, 0.8181262, 0.26284897].as_ref()
         );
     }
 
+    #[test]
+    fn random_with_zero_bias() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let init = Initializer::new(WeightInit::Xavier).with_zero_bias();
+        let layer = Layer::random(&mut rng, 4, 2, LayerKind::Dense, Activation::Tanh, init);
+        let limit = (6.0_f32 / 6.0).sqrt();
+        for row in layer.params.chunks(5) {
+            assert_eq!(row[0], 0.0);
+            assert!(row[1..].iter().all(|weight| weight.abs() <= limit));
+        }
+    }
+
     #[test]
     fn propagate_neuron() {
-        // let mut rng = ChaCha8Rng::from_seed(Default::default());  
-        let neuron = Neuron {
-            bias: 0.5,
-            weights: vec![-0.3 , 0.8],
-        };
+        let neuron = |activation| layer(&[(0.5, &[-0.3, 0.8])], activation);
         assert_relative_eq!(
-            neuron.propagate(&[-10.0, -10.0]),
+            neuron(Activation::Relu).propagate(vec![-10.0, -10.0])[0],
             0.0
         );
 
         assert_relative_eq!(
-            neuron.propagate(&[0.6, 0.3]),
+            neuron(Activation::Relu).propagate(vec![0.6, 0.3])[0],
             (0.6 * -0.3 + 0.3 * 0.8 + 0.5f32).max(0.0)
         );
+
+        assert_relative_eq!(
+            neuron(Activation::Tanh).propagate(vec![-10.0, -10.0])[0],
+            (-10.0 * -0.3 + -10.0 * 0.8 + 0.5f32).tanh()
+        );
     }
     
     #[test]
     fn propagate_layer() {
-        let neurons = vec![
-            Neuron {
-                bias: 0.1, 
-                weights: vec![0.1, -0.3, 0.2]
-            },
-            Neuron {
-                bias: -0.2,
-                weights: vec![0.4, 0.1, -0.5]
-            }
-        ];
-
-        let layer = Layer {
-            neurons,
-        };
+        let layer = layer(
+            &[
+                (0.1, &[0.1, -0.3, 0.2]),
+                (-0.2, &[0.4, 0.1, -0.5]),
+            ],
+            Activation::Relu,
+        );
         
         let inputs = vec![0.5_f32, -0.2, 0.1];
         let expected_outputs = [0.23_f32, 0.0];
@@ -224,42 +361,27 @@ mod test {
     #[test]
     fn propagate_network() {
         let layers = vec![
-            Layer {
-                neurons: vec![
-                    Neuron {
-                        bias: 0.1, 
-                        weights: vec![0.1, -0.3, 0.2]
-                    },
-                    Neuron {
-                        bias: -0.2,
-                        weights: vec![0.4, 0.1, -0.5]
-                    },
-                ]
-            },
-            Layer {
-                neurons: vec![
-                    Neuron {
-                        bias: 0.1,
-                        weights: vec![0.2, -0.4]
-                    },
-                    Neuron {
-                        bias: 0.2,
-                        weights: vec![-0.3, 0.1]
-                    },
-                    Neuron {
-                        bias: -0.1,
-                        weights: vec![0.5, 0.2]
-                    },
-                ]
-            },
-            Layer {
-                neurons: vec![
-                    Neuron {
-                        bias: 0.1,
-                        weights: vec![0.3, -0.2, 0.1]
-                    }
-                ]
-            }
+            layer(
+                &[
+                    (0.1, &[0.1, -0.3, 0.2]),
+                    (-0.2, &[0.4, 0.1, -0.5]),
+                ],
+                Activation::Relu,
+            ),
+            layer(
+                &[
+                    (0.1, &[0.2, -0.4]),
+                    (0.2, &[-0.3, 0.1]),
+                    (-0.1, &[0.5, 0.2]),
+                ],
+                Activation::Relu,
+            ),
+            layer(
+                &[
+                    (0.1, &[0.3, -0.2, 0.1]),
+                ],
+                Activation::Relu,
+            ),
         ];
         
         let network = Network {
@@ -272,6 +394,101 @@ mod test {
 
         assert_relative_eq!(actual_output.as_slice(), expected_output.as_ref());
     }
+
+    mod try_from_params {
+        use super::*;
+
+        fn layers(sizes: &[usize]) -> Vec<LayerTopology> {
+            sizes
+                .iter()
+                .map(|&num_neuron| LayerTopology {num_neuron, activation: Activation::Relu, kind: LayerKind::Dense})
+                .collect()
+        }
+
+        #[test]
+        fn too_many_params() {
+            let actual = Network::try_from_params(&layers(&[3, 2]), vec![0.0; 9]).unwrap_err();
+            assert_eq!(actual, NetworkError::ParamCount { expected: 8, actual: 9 });
+        }
+
+        #[test]
+        fn not_enough_params() {
+            let actual = Network::try_from_params(&layers(&[3, 2]), vec![0.0; 7]).unwrap_err();
+            assert_eq!(actual, NetworkError::ParamCount { expected: 8, actual: 7 });
+        }
+
+        #[test]
+        fn empty_topology() {
+            let actual = Network::try_from_params(&layers(&[3]), vec![]).unwrap_err();
+            assert_eq!(actual, NetworkError::EmptyTopology);
+        }
+
+        #[test]
+        fn empty_layer() {
+            let actual = Network::try_from_params(&layers(&[3, 0, 2]), vec![]).unwrap_err();
+            assert_eq!(actual, NetworkError::EmptyLayer { layer: 1 });
+        }
+    }
+
+    #[test]
+    fn neuron_blocks() {
+        let layers = vec![
+            LayerTopology {num_neuron: 3, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 1, activation: Activation::Tanh, kind: LayerKind::Gated},
+        ];
+        let blocks = Network::neuron_blocks(&layers);
+        assert_eq!(blocks, vec![4, 4, 8]);
+        assert_eq!(blocks.iter().sum::<usize>(), Network::param_count(&layers));
+    }
+
+    #[test]
+    fn gated_neuron_blocks() {
+        let layers = vec![
+            LayerTopology {num_neuron: 1, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Gated},
+        ];
+        // each row: bias, input weight, two recurrent weights; candidate then gate per neuron
+        let blocks = Network::neuron_blocks(&layers);
+        assert_eq!(blocks, vec![8, 8]);
+
+        // the second block alone moves the second neuron: its candidate, its gate and both recurrent rows
+        let params: Vec<f32> = (0..16).map(|n| n as f32).collect();
+        let network = Network::from_params(&layers, params);
+        let neurons: Vec<_> = network.layers()[0].neurons().collect();
+        assert_relative_eq!(neurons[1].bias(), 8.0);
+        assert_relative_eq!(neurons[3].bias(), 12.0);
+        assert_relative_eq!(neurons[3].recurrent_weights(), [14.0, 15.0].as_ref());
+    }
+
+    #[test]
+    fn try_propagate() {
+        let layers = vec![
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 1, activation: Activation::Identity, kind: LayerKind::Dense},
+        ];
+        let network = Network::from_params(&layers, vec![0.5, 1.0, -1.0]);
+
+        assert_eq!(network.try_propagate(vec![2.0, 1.0]), Ok(vec![1.5]));
+        assert_eq!(
+            network.try_propagate(vec![2.0, 1.0, 0.0]),
+            Err(NetworkError::InputWidth { expected: 2, actual: 3 })
+        );
+    }
+
+    #[test]
+    fn propagate_network_with_negative_outputs() {
+        let layers = vec![
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Identity, kind: LayerKind::Dense},
+        ];
+
+        let network = Network::from_params(&layers, vec![-0.5, 0.2, 0.1, 0.0, -1.0, 1.0]);
+        let actual_output = network.propagate(vec![1.0, 2.0]);
+        let expected_output = [-0.1_f32, 1.0];
+
+        assert_relative_eq!(actual_output.as_slice(), expected_output.as_ref());
+    }
 }
 
 // fn main() {
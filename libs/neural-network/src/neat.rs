@@ -0,0 +1,499 @@
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+// How many random node pairs `mutate_add_connection` tries before giving up on a (nearly) full genome
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+
+// Chance a gene disabled in either parent stays disabled in the child
+const KEEP_DISABLED_CHANCE: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    Input,
+    Hidden,
+    Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub bias: f32, // ignored for input nodes
+    pub activation: Activation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+// Historical markings shared by a whole population, so the same structural change gets the same ids everywhere
+#[derive(Debug, Clone, Default)]
+pub struct Innovations {
+    next_node: usize,
+    next_innovation: usize,
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>, // split connection innovation -> new node id
+}
+
+impl Innovations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn split(&mut self, innovation: usize) -> usize {
+        let next = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn reserve_nodes(&mut self, count: usize) {
+        self.next_node = self.next_node.max(count);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Genome {
+    nodes: Vec<NodeGene>, // inputs first, then outputs, then hidden nodes in order of creation
+    connections: Vec<ConnectionGene>, // sorted by innovation
+}
+
+impl Genome {
+    // Every input wired straight to every output; node ids 0..inputs+outputs are the same in every minimal genome
+    pub fn minimal(
+        rng: &mut dyn RngCore,
+        innovations: &mut Innovations,
+        input_size: usize,
+        output_size: usize,
+        activation: Activation,
+    ) -> Self {
+        assert!(input_size > 0 && output_size > 0);
+        innovations.reserve_nodes(input_size + output_size);
+
+        let inputs = (0..input_size).map(|id| NodeGene {
+            id,
+            kind: NodeKind::Input,
+            bias: 0.0,
+            activation: Activation::Identity,
+        });
+        let outputs = (input_size..input_size + output_size).map(|id| NodeGene {
+            id,
+            kind: NodeKind::Output,
+            bias: rng.gen_range(-1.0..=1.0),
+            activation,
+        });
+        let nodes: Vec<_> = inputs.chain(outputs).collect();
+
+        let mut connections = Vec::with_capacity(input_size * output_size);
+        for to in input_size..input_size + output_size {
+            for from in 0..input_size {
+                connections.push(ConnectionGene {
+                    innovation: innovations.connection(from, to),
+                    from,
+                    to,
+                    weight: rng.gen_range(-1.0..=1.0),
+                    enabled: true,
+                });
+            }
+        }
+        connections.sort_by_key(|connection| connection.innovation);
+
+        Self { nodes, connections }
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.nodes.iter().filter(|node| node.kind == NodeKind::Input).count()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.nodes.iter().filter(|node| node.kind == NodeKind::Output).count()
+    }
+
+    pub fn mutate_weights(&mut self, rng: &mut dyn RngCore, chance: f32, coeff: f32) {
+        let weights = self.connections.iter_mut().map(|connection| &mut connection.weight);
+        let biases = self
+            .nodes
+            .iter_mut()
+            .filter(|node| node.kind != NodeKind::Input)
+            .map(|node| &mut node.bias);
+
+        for value in weights.chain(biases) {
+            if rng.gen_bool(chance as f64) {
+                let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                *value += sign * coeff * rng.gen::<f32>();
+            }
+        }
+    }
+
+    // Wires two unconnected nodes together, never closing a cycle; returns false if no such pair was found
+    pub fn mutate_add_connection(&mut self, rng: &mut dyn RngCore, innovations: &mut Innovations) -> bool {
+        for _ in 0..ADD_CONNECTION_ATTEMPTS {
+            let from = self.nodes.choose(rng).unwrap();
+            let to = self.nodes.choose(rng).unwrap();
+
+            if from.kind == NodeKind::Output
+                || to.kind == NodeKind::Input
+                || from.id == to.id
+                || self.connections.iter().any(|c| c.from == from.id && c.to == to.id)
+                || self.reaches(to.id, from.id)
+            {
+                continue;
+            }
+
+            let (from, to) = (from.id, to.id);
+            self.insert_connection(ConnectionGene {
+                innovation: innovations.connection(from, to),
+                from,
+                to,
+                weight: rng.gen_range(-1.0..=1.0),
+                enabled: true,
+            });
+            return true;
+        }
+
+        false
+    }
+
+    // Splits an enabled connection a -> b into a -> new -> b, keeping the old behaviour as close as possible
+    pub fn mutate_add_node(
+        &mut self,
+        rng: &mut dyn RngCore,
+        innovations: &mut Innovations,
+        activation: Activation,
+    ) -> bool {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        let Some(&index) = enabled.choose(rng) else {
+            return false;
+        };
+
+        let split = self.connections[index];
+        let id = innovations.split(split.innovation);
+        if self.nodes.iter().any(|node| node.id == id) {
+            return false;
+        }
+
+        self.connections[index].enabled = false;
+        self.nodes.push(NodeGene { id, kind: NodeKind::Hidden, bias: 0.0, activation });
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(split.from, id),
+            from: split.from,
+            to: id,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(id, split.to),
+            from: id,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+        true
+    }
+
+    // Matching genes come from either parent at random, disjoint and excess genes only from the fitter one
+    pub fn crossover(rng: &mut dyn RngCore, fitter: &Genome, other: &Genome) -> Genome {
+        let other_connections: HashMap<usize, &ConnectionGene> = other
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation, connection))
+            .collect();
+        let other_nodes: HashMap<usize, &NodeGene> =
+            other.nodes.iter().map(|node| (node.id, node)).collect();
+
+        let connections = fitter
+            .connections
+            .iter()
+            .map(|connection| match other_connections.get(&connection.innovation) {
+                Some(&matching) => {
+                    let mut child = if rng.gen_bool(0.5) { *connection } else { *matching };
+                    let disabled = !connection.enabled || !matching.enabled;
+                    child.enabled = !(disabled && rng.gen_bool(KEEP_DISABLED_CHANCE));
+                    child
+                }
+                None => *connection,
+            })
+            .collect();
+
+        let nodes = fitter
+            .nodes
+            .iter()
+            .map(|node| match other_nodes.get(&node.id) {
+                Some(&matching) if rng.gen_bool(0.5) => *matching,
+                _ => *node,
+            })
+            .collect();
+
+        Genome { nodes, connections }
+    }
+
+    pub fn compile(&self) -> GraphNetwork {
+        let index_of: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+
+        let mut incoming = vec![Vec::new(); self.nodes.len()];
+        for connection in self.connections.iter().filter(|connection| connection.enabled) {
+            incoming[index_of[&connection.to]].push((index_of[&connection.from], connection.weight));
+        }
+
+        // Kahn's algorithm; inputs have no incoming connections so they always come first
+        let mut pending: Vec<usize> = incoming.iter().map(Vec::len).collect();
+        let mut order: Vec<usize> = (0..self.nodes.len()).filter(|&index| pending[index] == 0).collect();
+        let mut cursor = 0;
+        while cursor < order.len() {
+            let from = order[cursor];
+            cursor += 1;
+            for (to, sources) in incoming.iter().enumerate() {
+                for _ in sources.iter().filter(|(source, _)| *source == from) {
+                    pending[to] -= 1;
+                    if pending[to] == 0 {
+                        order.push(to);
+                    }
+                }
+            }
+        }
+        assert_eq!(order.len(), self.nodes.len(), "genome should stay acyclic");
+
+        let nodes = order
+            .into_iter()
+            .filter(|&index| self.nodes[index].kind != NodeKind::Input)
+            .map(|index| GraphNode {
+                index,
+                bias: self.nodes[index].bias,
+                activation: self.nodes[index].activation,
+                incoming: std::mem::take(&mut incoming[index]),
+            })
+            .collect();
+
+        let filter_kind = |kind| {
+            self.nodes
+                .iter()
+                .enumerate()
+                .filter(move |(_, node)| node.kind == kind)
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        };
+
+        GraphNetwork {
+            width: self.nodes.len(),
+            inputs: filter_kind(NodeKind::Input),
+            outputs: filter_kind(NodeKind::Output),
+            nodes,
+        }
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let position = self
+            .connections
+            .partition_point(|existing| existing.innovation < connection.innovation);
+        self.connections.insert(position, connection);
+    }
+
+    // Is there an enabled or disabled path from -> .. -> to? Disabled genes count, since crossover may re-enable them
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut seen = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for connection in self.connections.iter().filter(|connection| connection.from == node) {
+                if !seen.contains(&connection.to) {
+                    seen.push(connection.to);
+                    stack.push(connection.to);
+                }
+            }
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GraphNode {
+    index: usize,
+    bias: f32,
+    activation: Activation,
+    incoming: Vec<(usize, f32)>, // (source node index, weight)
+}
+
+// Executable form of a `Genome`: non-input nodes in topological order over a flat value buffer
+#[derive(Debug, Clone)]
+pub struct GraphNetwork {
+    width: usize,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    nodes: Vec<GraphNode>,
+}
+
+impl GraphNetwork {
+    pub fn input_size(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.input_size());
+
+        let mut values = vec![0.0; self.width];
+        for (&index, &input) in self.inputs.iter().zip(inputs) {
+            values[index] = input;
+        }
+
+        for node in &self.nodes {
+            let sum = node
+                .incoming
+                .iter()
+                .map(|&(source, weight)| values[source] * weight)
+                .sum::<f32>();
+            values[node.index] = node.activation.apply(sum + node.bias);
+        }
+
+        self.outputs.iter().map(|&index| values[index]).collect()
+    }
+
+    pub fn try_propagate(&self, inputs: &[f32]) -> Result<Vec<f32>, NetworkError> {
+        let expected = self.input_size();
+        if inputs.len() != expected {
+            return Err(NetworkError::InputWidth { expected, actual: inputs.len() });
+        }
+
+        Ok(self.propagate(inputs))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn genome(rng: &mut ChaCha8Rng, innovations: &mut Innovations) -> Genome {
+        Genome::minimal(rng, innovations, 2, 1, Activation::Identity)
+    }
+
+    #[test]
+    fn minimal_compiles_to_weighted_sum() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut innovations = Innovations::new();
+        let genome = genome(&mut rng, &mut innovations);
+
+        let bias = genome.nodes()[2].bias;
+        let (a, b) = (genome.connections()[0].weight, genome.connections()[1].weight);
+        let actual = genome.compile().propagate(&[1.0, 2.0]);
+        assert_eq!(actual.len(), 1);
+        assert_relative_eq!(actual[0], bias + a + 2.0 * b);
+    }
+
+    #[test]
+    fn add_node_keeps_behaviour_for_identity() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut innovations = Innovations::new();
+        let mut genome = genome(&mut rng, &mut innovations);
+        let before = genome.compile().propagate(&[0.3, -0.7]);
+
+        assert!(genome.mutate_add_node(&mut rng, &mut innovations, Activation::Identity));
+        assert_eq!(genome.nodes().len(), 4);
+        assert_eq!(genome.connections().len(), 4);
+        assert_eq!(genome.connections().iter().filter(|c| !c.enabled).count(), 1);
+
+        let after = genome.compile().propagate(&[0.3, -0.7]);
+        assert_relative_eq!(after.as_slice(), before.as_slice());
+    }
+
+    #[test]
+    fn same_structural_mutation_shares_innovation() {
+        let mut innovations = Innovations::new();
+        let mut a = genome(&mut ChaCha8Rng::from_seed(Default::default()), &mut innovations);
+        let mut b = genome(&mut ChaCha8Rng::from_seed(Default::default()), &mut innovations);
+
+        a.mutate_add_node(&mut ChaCha8Rng::from_seed([1; 32]), &mut innovations, Activation::Relu);
+        b.mutate_add_node(&mut ChaCha8Rng::from_seed([1; 32]), &mut innovations, Activation::Relu);
+
+        let innovations_of = |genome: &Genome| {
+            genome.connections().iter().map(|c| c.innovation).collect::<Vec<_>>()
+        };
+        assert_eq!(innovations_of(&a), innovations_of(&b));
+        assert_eq!(a.nodes()[3].id, b.nodes()[3].id);
+    }
+
+    #[test]
+    fn add_connection_stays_acyclic() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut innovations = Innovations::new();
+        let mut genome = Genome::minimal(&mut rng, &mut innovations, 3, 2, Activation::Tanh);
+
+        for _ in 0..10 {
+            genome.mutate_add_node(&mut rng, &mut innovations, Activation::Relu);
+            genome.mutate_add_connection(&mut rng, &mut innovations);
+        }
+
+        let sorted = genome.connections().windows(2).all(|pair| pair[0].innovation < pair[1].innovation);
+        assert!(sorted);
+        assert_eq!(genome.compile().propagate(&[0.1, 0.2, 0.3]).len(), 2);
+    }
+
+    #[test]
+    fn crossover_takes_structure_from_fitter_parent() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut innovations = Innovations::new();
+        let other = genome(&mut rng, &mut innovations);
+        let mut fitter = other.clone();
+        fitter.mutate_add_node(&mut rng, &mut innovations, Activation::Relu);
+
+        let child = Genome::crossover(&mut rng, &fitter, &other);
+        assert_eq!(child.nodes().len(), fitter.nodes().len());
+        assert_eq!(child.connections().len(), fitter.connections().len());
+
+        let child = Genome::crossover(&mut rng, &other, &fitter);
+        assert_eq!(child.nodes().len(), other.nodes().len());
+        assert_eq!(child.connections().len(), other.connections().len());
+    }
+
+    #[test]
+    fn try_propagate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = genome(&mut rng, &mut Innovations::new()).compile();
+        assert_eq!(
+            network.try_propagate(&[1.0]),
+            Err(NetworkError::InputWidth { expected: 2, actual: 1 })
+        );
+    }
+}
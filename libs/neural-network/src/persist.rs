@@ -0,0 +1,407 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::iter::once;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+pub const FORMAT_VERSION: u32 = 3; // 3: gate rows next to their neuron; 2: layer kinds; version 1 files are all dense
+
+const BINARY_MAGIC: [u8; 4] = *b"NNET";
+
+// On-disk brain: everything needed to rebuild the `Network` without any external topology
+#[derive(Debug, Serialize, Deserialize)]
+struct NetworkFile {
+    version: u32,
+    topology: Vec<LayerTopology>,
+    params: Vec<f32>,
+}
+
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Truncated,
+    UnknownActivation(u8),
+    UnknownLayerKind(u8),
+    Network(NetworkError),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Json(err) => write!(f, "json error: {}", err),
+            Self::BadMagic => write!(f, "not a network file"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            Self::Truncated => write!(f, "file ended unexpectedly"),
+            Self::UnknownActivation(tag) => write!(f, "unknown activation tag {}", tag),
+            Self::UnknownLayerKind(tag) => write!(f, "unknown layer kind tag {}", tag),
+            Self::Network(err) => write!(f, "invalid network: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+impl From<io::Error> for PersistError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<NetworkError> for PersistError {
+    fn from(err: NetworkError) -> Self {
+        Self::Network(err)
+    }
+}
+
+impl NetworkFile {
+    fn from_network(network: &Network) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            topology: network.topology(),
+            params: network.get_params(),
+        }
+    }
+
+    fn into_network(self) -> Result<Network, PersistError> {
+        if !(1..=FORMAT_VERSION).contains(&self.version) {
+            return Err(PersistError::UnsupportedVersion(self.version));
+        }
+
+        let params = if self.version < 3 {
+            interleave_gate_rows(&self.topology, self.params)
+        } else {
+            self.params
+        };
+        Ok(Network::try_from_params(&self.topology, params)?)
+    }
+}
+
+// Version 2 stored a gated layer as every candidate row, then every gate row; since 3 each gate row follows its candidate.
+// Layers with the wrong number of params are copied as they are, for `try_from_params` to report.
+fn interleave_gate_rows(topology: &[LayerTopology], params: Vec<f32>) -> Vec<f32> {
+    let mut converted = Vec::with_capacity(params.len());
+    let mut rest = params.as_slice();
+
+    for adjacent_layers in topology.windows(2) {
+        let (input_size, output_size) = (adjacent_layers[0].num_neuron, adjacent_layers[1].num_neuron);
+        let kind = adjacent_layers[1].kind;
+        let param_count = kind.param_count(input_size, output_size);
+        let (layer, tail) = rest.split_at(param_count.min(rest.len()));
+        rest = tail;
+
+        if kind != LayerKind::Gated || layer.len() != param_count || output_size == 0 {
+            converted.extend_from_slice(layer);
+            continue;
+        }
+        let rows: Vec<&[f32]> = layer.chunks(kind.row_size(input_size, output_size)).collect();
+        for neuron in 0..output_size {
+            converted.extend_from_slice(rows[neuron]);
+            converted.extend_from_slice(rows[output_size + neuron]);
+        }
+    }
+
+    converted.extend_from_slice(rest);
+    converted
+}
+
+impl Network {
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        once(LayerTopology {
+            num_neuron: self.input_size(),
+            activation: Activation::default(),
+            kind: LayerKind::default(),
+        })
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                num_neuron: layer.output_size,
+                activation: layer.activation,
+                kind: layer.kind,
+            }))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&NetworkFile::from_network(self))
+            .expect("network should always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PersistError> {
+        serde_json::from_str::<NetworkFile>(json)?.into_network()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let file = NetworkFile::from_network(self);
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&BINARY_MAGIC);
+        bytes.extend_from_slice(&file.version.to_le_bytes());
+        bytes.extend_from_slice(&(file.topology.len() as u32).to_le_bytes());
+        for layer in &file.topology {
+            let (tag, alpha) = activation_to_tag(layer.activation);
+            bytes.extend_from_slice(&(layer.num_neuron as u32).to_le_bytes());
+            bytes.push(tag);
+            bytes.extend_from_slice(&alpha.to_le_bytes());
+            bytes.push(layer_kind_to_tag(layer.kind));
+        }
+        bytes.extend_from_slice(&(file.params.len() as u32).to_le_bytes());
+        for param in &file.params {
+            bytes.extend_from_slice(&param.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PersistError> {
+        let mut reader = ByteReader { bytes };
+
+        if reader.take(4)? != BINARY_MAGIC {
+            return Err(PersistError::BadMagic);
+        }
+        let version = reader.read_u32()?;
+        if !(1..=FORMAT_VERSION).contains(&version) {
+            return Err(PersistError::UnsupportedVersion(version));
+        }
+
+        let layer_num = reader.read_u32()? as usize;
+        let topology = (0..layer_num)
+            .map(|_| {
+                let num_neuron = reader.read_u32()? as usize;
+                let tag = reader.take(1)?[0];
+                let alpha = reader.read_f32()?;
+                let kind = if version >= 2 {
+                    layer_kind_from_tag(reader.take(1)?[0])?
+                } else {
+                    LayerKind::Dense
+                };
+                Ok(LayerTopology { num_neuron, activation: activation_from_tag(tag, alpha)?, kind })
+            })
+            .collect::<Result<Vec<_>, PersistError>>()?;
+
+        let param_num = reader.read_u32()? as usize;
+        let params = (0..param_num)
+            .map(|_| reader.read_f32())
+            .collect::<Result<Vec<_>, PersistError>>()?;
+
+        NetworkFile { version, topology, params }.into_network()
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), PersistError> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, PersistError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save_binary(&self, path: impl AsRef<Path>) -> Result<(), PersistError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_binary(path: impl AsRef<Path>) -> Result<Self, PersistError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+fn activation_to_tag(activation: Activation) -> (u8, f32) {
+    match activation {
+        Activation::Relu => (0, 0.0),
+        Activation::LeakyRelu { alpha } => (1, alpha),
+        Activation::Sigmoid => (2, 0.0),
+        Activation::Tanh => (3, 0.0),
+        Activation::Identity => (4, 0.0),
+        Activation::Softsign => (5, 0.0),
+        Activation::Step => (6, 0.0),
+    }
+}
+
+fn activation_from_tag(tag: u8, alpha: f32) -> Result<Activation, PersistError> {
+    match tag {
+        0 => Ok(Activation::Relu),
+        1 => Ok(Activation::LeakyRelu { alpha }),
+        2 => Ok(Activation::Sigmoid),
+        3 => Ok(Activation::Tanh),
+        4 => Ok(Activation::Identity),
+        5 => Ok(Activation::Softsign),
+        6 => Ok(Activation::Step),
+        _ => Err(PersistError::UnknownActivation(tag)),
+    }
+}
+
+fn layer_kind_to_tag(kind: LayerKind) -> u8 {
+    match kind {
+        LayerKind::Dense => 0,
+        LayerKind::Elman => 1,
+        LayerKind::Gated => 2,
+    }
+}
+
+fn layer_kind_from_tag(tag: u8) -> Result<LayerKind, PersistError> {
+    match tag {
+        0 => Ok(LayerKind::Dense),
+        1 => Ok(LayerKind::Elman),
+        2 => Ok(LayerKind::Gated),
+        _ => Err(PersistError::UnknownLayerKind(tag)),
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PersistError> {
+        if self.bytes.len() < len {
+            return Err(PersistError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, PersistError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_f32(&mut self) -> Result<f32, PersistError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network() -> Network {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        Network::random(&mut rng, &[
+            LayerTopology {num_neuron: 3, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 4, activation: Activation::LeakyRelu { alpha: 0.01 }, kind: LayerKind::Elman},
+            LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Dense},
+        ])
+    }
+
+    fn assert_same_network(actual: &Network, expected: &Network) {
+        assert_eq!(actual.topology().len(), expected.topology().len());
+        for (a, b) in actual.topology().iter().zip(expected.topology().iter()) {
+            assert_eq!(a.num_neuron, b.num_neuron);
+            assert_eq!(a.activation, b.activation);
+            assert_eq!(a.kind, b.kind);
+        }
+        let (actual, expected) = (actual.get_params(), expected.get_params());
+        assert_relative_eq!(actual.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let expected = network();
+        let actual = Network::from_json(&expected.to_json()).unwrap();
+        assert_same_network(&actual, &expected);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let expected = network();
+        let actual = Network::from_bytes(&expected.to_bytes()).unwrap();
+        assert_same_network(&actual, &expected);
+    }
+
+    #[test]
+    fn file_roundtrip() {
+        let expected = network();
+        let dir = std::env::temp_dir();
+        let json_path = dir.join(format!("nn-persist-{}.json", std::process::id()));
+        let binary_path = dir.join(format!("nn-persist-{}.bin", std::process::id()));
+
+        expected.save_json(&json_path).unwrap();
+        expected.save_binary(&binary_path).unwrap();
+        let from_json = Network::load_json(&json_path).unwrap();
+        let from_binary = Network::load_binary(&binary_path).unwrap();
+        fs::remove_file(json_path).unwrap();
+        fs::remove_file(binary_path).unwrap();
+
+        assert_same_network(&from_json, &expected);
+        assert_same_network(&from_binary, &expected);
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("nn-persist-does-not-exist.bin");
+        assert!(matches!(Network::load_binary(path), Err(PersistError::Io(_))));
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut bytes = network().to_bytes();
+        bytes[0] = b'X';
+        assert!(matches!(Network::from_bytes(&bytes), Err(PersistError::BadMagic)));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let json = network().to_json().replace("\"version\": 3", "\"version\": 99");
+        assert!(matches!(Network::from_json(&json), Err(PersistError::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn version_2_gate_rows() {
+        // every candidate row, then every gate row: bias, input weight, two recurrent weights each
+        let params: Vec<String> = (0..16).map(|n| format!("{}.0", n)).collect();
+        let json = format!(r#"{{
+            "version": 2,
+            "topology": [
+                {{"num_neuron": 1, "activation": "Relu"}},
+                {{"num_neuron": 2, "activation": "Tanh", "kind": "Gated"}}
+            ],
+            "params": [{}]
+        }}"#, params.join(", "));
+        let network = Network::from_json(&json).unwrap();
+
+        let biases: Vec<f32> = network.layers()[0].neurons().map(|neuron| neuron.bias()).collect();
+        assert_relative_eq!(biases.as_slice(), [0.0, 4.0, 8.0, 12.0].as_ref());
+        // stored again with the first gate row right after the first candidate row
+        let params = network.get_params();
+        assert_relative_eq!(params[4..8].as_ref(), [8.0, 9.0, 10.0, 11.0].as_ref());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = network().to_bytes();
+        let bytes = &bytes[..bytes.len() - 2];
+        assert!(matches!(Network::from_bytes(bytes), Err(PersistError::Truncated)));
+    }
+
+    #[test]
+    fn wrong_param_count() {
+        let json = r#"{
+            "version": 1,
+            "topology": [
+                {"num_neuron": 2, "activation": "Relu"},
+                {"num_neuron": 1, "activation": "Identity"}
+            ],
+            "params": [0.1, 0.2]
+        }"#;
+        assert!(matches!(
+            Network::from_json(json),
+            Err(PersistError::Network(NetworkError::ParamCount { expected: 3, actual: 2 }))
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use std::mem;
+
+use crate::*;
+
+// Reusable buffers for `Network::propagate_into`; keep one around per caller so the hot loop never allocates
+#[derive(Debug, Clone, Default)]
+pub struct Scratch {
+    front: Vec<f32>,
+    back: Vec<f32>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn for_network(network: &Network) -> Self {
+        let width = network.max_width();
+        Self {
+            front: vec![0.0; width],
+            back: vec![0.0; width],
+        }
+    }
+
+    fn reserve(&mut self, width: usize) {
+        if self.front.len() < width {
+            self.front.resize(width, 0.0);
+            self.back.resize(width, 0.0);
+        }
+    }
+}
+
+impl Network {
+    pub fn propagate_into(&self, input: &[f32], out: &mut [f32], scratch: &mut Scratch) {
+        self.propagate_layers(input, out, scratch, None);
+    }
+
+    // Recurrent layers read their previous outputs from `state` and write the new ones back; dense layers ignore it
+    pub(crate) fn propagate_layers(
+        &self,
+        input: &[f32],
+        out: &mut [f32],
+        scratch: &mut Scratch,
+        mut state: Option<&mut NetworkState>,
+    ) {
+        assert_eq!(input.len(), self.input_size());
+        assert_eq!(out.len(), self.output_size());
+
+        scratch.reserve(self.max_width());
+        scratch.front[..input.len()].copy_from_slice(input);
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let outputs = &mut scratch.back[..layer.output_size];
+            let hidden = state.as_deref_mut().map(|state| &mut state.hidden[index]);
+
+            match hidden {
+                Some(hidden) if layer.kind.is_recurrent() => {
+                    layer.propagate_into(&scratch.front[..layer.input_size], Some(hidden), outputs);
+                    hidden.copy_from_slice(outputs);
+                }
+                _ => layer.propagate_into(&scratch.front[..layer.input_size], None, outputs),
+            }
+            mem::swap(&mut scratch.front, &mut scratch.back);
+        }
+
+        out.copy_from_slice(&scratch.front[..out.len()]);
+    }
+
+    // Many inputs through one network: `inputs` and `outputs` are row-major, one row per sample
+    pub fn propagate_batch(&self, inputs: &[f32], outputs: &mut [f32], scratch: &mut Scratch) {
+        let (input_size, output_size) = (self.input_size(), self.output_size());
+        assert_eq!(inputs.len() % input_size, 0);
+        assert_eq!(outputs.len() % output_size, 0);
+        assert_eq!(inputs.len() / input_size, outputs.len() / output_size);
+
+        for (input, output) in inputs
+            .chunks_exact(input_size)
+            .zip(outputs.chunks_exact_mut(output_size))
+        {
+            self.propagate_into(input, output, scratch);
+        }
+    }
+
+    // One input row per network, e.g. every creature's vision in a single pass; all networks must share a topology
+    pub fn propagate_many(networks: &[Network], inputs: &[f32], outputs: &mut [f32], scratch: &mut Scratch) {
+        if networks.is_empty() {
+            return;
+        }
+
+        let (input_size, output_size) = (networks[0].input_size(), networks[0].output_size());
+        assert_eq!(inputs.len(), networks.len() * input_size);
+        assert_eq!(outputs.len(), networks.len() * output_size);
+
+        for ((network, input), output) in networks
+            .iter()
+            .zip(inputs.chunks_exact(input_size))
+            .zip(outputs.chunks_exact_mut(output_size))
+        {
+            network.propagate_into(input, output, scratch);
+        }
+    }
+
+    fn max_width(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.output_size)
+            .fold(self.input_size(), usize::max)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network(rng: &mut ChaCha8Rng) -> Network {
+        Network::random(rng, &[
+            LayerTopology {num_neuron: 3, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 5, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Dense},
+        ])
+    }
+
+    #[test]
+    fn propagate_into_matches_propagate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = network(&mut rng);
+        let mut scratch = Scratch::new();
+        let mut actual = [0.0; 2];
+
+        for input in [[0.5, -0.2, 0.1], [1.0, 1.0, 1.0], [-3.0, 0.0, 2.0]] {
+            network.propagate_into(&input, &mut actual, &mut scratch);
+            let expected = network.propagate(input.to_vec());
+            assert_relative_eq!(actual.as_ref(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    fn propagate_batch() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = network(&mut rng);
+        let mut scratch = Scratch::for_network(&network);
+
+        let inputs = [0.5, -0.2, 0.1, 1.0, 1.0, 1.0];
+        let mut actual = [0.0; 4];
+        network.propagate_batch(&inputs, &mut actual, &mut scratch);
+
+        let expected: Vec<f32> = inputs
+            .chunks(3)
+            .flat_map(|input| network.propagate(input.to_vec()))
+            .collect();
+        assert_relative_eq!(actual.as_ref(), expected.as_slice());
+    }
+
+    #[test]
+    fn propagate_many() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let networks = vec![network(&mut rng), network(&mut rng), network(&mut rng)];
+        let mut scratch = Scratch::new();
+
+        let inputs = [0.5, -0.2, 0.1, 1.0, 1.0, 1.0, -3.0, 0.0, 2.0];
+        let mut actual = [0.0; 6];
+        Network::propagate_many(&networks, &inputs, &mut actual, &mut scratch);
+
+        let expected: Vec<f32> = networks
+            .iter()
+            .zip(inputs.chunks(3))
+            .flat_map(|(network, input)| network.propagate(input.to_vec()))
+            .collect();
+        assert_relative_eq!(actual.as_ref(), expected.as_slice());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::*;
+
+// Hidden state of every recurrent layer, owned by whoever drives the network (e.g. one per creature)
+#[derive(Debug, Clone, Default)]
+pub struct NetworkState {
+    pub(crate) hidden: Vec<Vec<f32>>, // one entry per layer, empty for dense layers
+}
+
+impl NetworkState {
+    pub fn for_network(network: &Network) -> Self {
+        let hidden = network
+            .layers
+            .iter()
+            .map(|layer| {
+                let size = if layer.kind.is_recurrent() { layer.output_size } else { 0 };
+                vec![0.0; size]
+            })
+            .collect();
+
+        Self { hidden }
+    }
+
+    pub fn reset(&mut self) {
+        for hidden in &mut self.hidden {
+            hidden.fill(0.0);
+        }
+    }
+}
+
+impl Network {
+    pub fn is_recurrent(&self) -> bool {
+        self.layers.iter().any(|layer| layer.kind.is_recurrent())
+    }
+
+    // Like `propagate_into`, but recurrent layers see their previous outputs and remember the new ones
+    pub fn propagate_stateful(
+        &self,
+        input: &[f32],
+        out: &mut [f32],
+        scratch: &mut Scratch,
+        state: &mut NetworkState,
+    ) {
+        assert_eq!(state.hidden.len(), self.layers.len());
+        self.propagate_layers(input, out, scratch, Some(state));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn layers(kind: LayerKind) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {num_neuron: 1, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 1, activation: Activation::Identity, kind},
+        ]
+    }
+
+    #[test]
+    fn elman_remembers_previous_output() {
+        // bias, input weight, recurrent weight
+        let network = Network::from_params(&layers(LayerKind::Elman), vec![0.0, 1.0, 0.5]);
+        let mut scratch = Scratch::new();
+        let mut state = NetworkState::for_network(&network);
+        let mut out = [0.0];
+
+        for expected in [1.0, 1.5, 1.75] {
+            network.propagate_stateful(&[1.0], &mut out, &mut scratch, &mut state);
+            assert_relative_eq!(out[0], expected);
+        }
+
+        state.reset();
+        network.propagate_stateful(&[1.0], &mut out, &mut scratch, &mut state);
+        assert_relative_eq!(out[0], 1.0);
+    }
+
+    #[test]
+    fn gated_interpolates_towards_candidate() {
+        // candidate row: bias, input weight, recurrent weight; gate row: bias 0 so z = 0.5
+        let network = Network::from_params(&layers(LayerKind::Gated), vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        let mut scratch = Scratch::new();
+        let mut state = NetworkState::for_network(&network);
+        let mut out = [0.0];
+
+        for expected in [0.5, 0.75, 0.875] {
+            network.propagate_stateful(&[1.0], &mut out, &mut scratch, &mut state);
+            assert_relative_eq!(out[0], expected);
+        }
+    }
+
+    #[test]
+    fn params_roundtrip() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let layers = vec![
+            LayerTopology {num_neuron: 3, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 4, activation: Activation::Tanh, kind: LayerKind::Gated},
+            LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Elman},
+        ];
+        let network = Network::random(&mut rng, &layers);
+        assert!(network.is_recurrent());
+        let params = network.get_params();
+        assert_eq!(params.len(), Network::param_count(&layers));
+        assert_eq!(params.len(), 2 * 4 * (1 + 3 + 4) + 2 * (1 + 4 + 2));
+
+        let rebuilt = Network::from_params(&layers, params.clone()).get_params();
+        assert_relative_eq!(rebuilt.as_slice(), params.as_slice());
+    }
+}
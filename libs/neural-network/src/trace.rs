@@ -0,0 +1,175 @@
+use crate::*;
+
+// One neuron's parameters, borrowed straight out of its layer
+#[derive(Debug, Clone, Copy)]
+pub struct Neuron<'a> {
+    row: &'a [f32], // [bias, input weights.., recurrent weights..]
+    input_size: usize,
+}
+
+impl<'a> Neuron<'a> {
+    pub fn bias(&self) -> f32 {
+        self.row[0]
+    }
+
+    pub fn weights(&self) -> &'a [f32] {
+        &self.row[1..=self.input_size]
+    }
+
+    // empty for dense layers
+    pub fn recurrent_weights(&self) -> &'a [f32] {
+        &self.row[1 + self.input_size..]
+    }
+}
+
+impl Layer {
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    pub fn kind(&self) -> LayerKind {
+        self.kind
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    // Gated layers list every candidate neuron first, then the matching update gates
+    pub fn neurons(&self) -> impl Iterator<Item = Neuron<'_>> {
+        (0..self.output_size * self.kind.gates()).map(move |index| Neuron {
+            row: self.row(index),
+            input_size: self.input_size,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerTrace {
+    pub pre_activation: Vec<f32>, // weighted sums; the candidate sums for gated layers
+    pub post_activation: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub input: Vec<f32>,
+    pub layers: Vec<LayerTrace>,
+}
+
+impl Trace {
+    pub fn output(&self) -> &[f32] {
+        self.layers.last().map_or(&self.input, |layer| &layer.post_activation)
+    }
+}
+
+impl Network {
+    // Same result as `propagate`, but keeps every layer's intermediate values around
+    pub fn propagate_traced(&self, inputs: &[f32]) -> Trace {
+        self.trace_layers(inputs, None)
+    }
+
+    // Same result as `propagate_stateful`, including the update of `state`, but traced
+    pub fn propagate_traced_stateful(&self, inputs: &[f32], state: &mut NetworkState) -> Trace {
+        assert_eq!(state.hidden.len(), self.layers.len());
+        self.trace_layers(inputs, Some(state))
+    }
+
+    fn trace_layers(&self, inputs: &[f32], mut state: Option<&mut NetworkState>) -> Trace {
+        assert_eq!(inputs.len(), self.input_size());
+
+        let mut layers: Vec<LayerTrace> = Vec::with_capacity(self.layers.len());
+        for (index, layer) in self.layers.iter().enumerate() {
+            let inputs = layers.last().map_or(inputs, |trace| &trace.post_activation);
+            let hidden = state
+                .as_deref_mut()
+                .map(|state| &mut state.hidden[index])
+                .filter(|_| layer.kind.is_recurrent());
+
+            let previous = hidden.as_deref().map(Vec::as_slice);
+
+            let pre_activation = (0..layer.output_size)
+                .map(|neuron| layer.weighted_sum(neuron, inputs, previous))
+                .collect();
+            let mut post_activation = vec![0.0; layer.output_size];
+            layer.propagate_into(inputs, previous, &mut post_activation);
+            if let Some(hidden) = hidden {
+                hidden.copy_from_slice(&post_activation);
+            }
+
+            layers.push(LayerTrace { pre_activation, post_activation });
+        }
+
+        Trace { input: inputs.to_vec(), layers }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network() -> Network {
+        let layers = vec![
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 1, activation: Activation::Tanh, kind: LayerKind::Dense},
+        ];
+        Network::from_params(&layers, vec![-0.5, 0.2, 0.1, 0.0, -1.0, 1.0, 0.1, 1.0, 1.0])
+    }
+
+    #[test]
+    fn propagate_traced() {
+        let network = network();
+        let trace = network.propagate_traced(&[1.0, 2.0]);
+
+        assert_eq!(trace.layers.len(), 2);
+        assert_relative_eq!(trace.layers[0].pre_activation.as_slice(), [-0.1, 1.0].as_ref());
+        assert_relative_eq!(trace.layers[0].post_activation.as_slice(), [0.0, 1.0].as_ref());
+        assert_relative_eq!(trace.layers[1].pre_activation.as_slice(), [1.1].as_ref());
+        let expected = network.propagate(vec![1.0, 2.0]);
+        assert_relative_eq!(trace.output(), expected.as_slice());
+    }
+
+    #[test]
+    fn propagate_traced_stateful() {
+        let layers = vec![
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 3, activation: Activation::Tanh, kind: LayerKind::Gated},
+            LayerTopology {num_neuron: 2, activation: Activation::Tanh, kind: LayerKind::Elman},
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &layers);
+
+        let mut scratch = Scratch::new();
+        let mut state = NetworkState::for_network(&network);
+        let mut traced_state = NetworkState::for_network(&network);
+        let mut out = [0.0; 2];
+
+        for input in [[1.0, 0.5], [0.0, -1.0], [0.3, 0.3]] {
+            network.propagate_stateful(&input, &mut out, &mut scratch, &mut state);
+            let trace = network.propagate_traced_stateful(&input, &mut traced_state);
+            assert_relative_eq!(trace.output(), out.as_ref());
+            for (traced, hidden) in traced_state.hidden.iter().zip(&state.hidden) {
+                assert_relative_eq!(traced.as_slice(), hidden.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn neurons() {
+        let network = network();
+        let layer = &network.layers()[0];
+        let neurons: Vec<_> = layer.neurons().collect();
+
+        assert_eq!(neurons.len(), 2);
+        assert_relative_eq!(neurons[1].bias(), 0.0);
+        assert_relative_eq!(neurons[1].weights(), [-1.0, 1.0].as_ref());
+        assert!(neurons[1].recurrent_weights().is_empty());
+    }
+}
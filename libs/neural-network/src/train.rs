@@ -0,0 +1,346 @@
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use crate::*;
+
+// Keeps cross-entropy finite when an output saturates at exactly 0 or 1
+const CROSS_ENTROPY_EPSILON: f32 = 1e-7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loss {
+    MeanSquaredError,
+    CrossEntropy, // binary, per output; expects outputs in (0, 1), e.g. from a sigmoid layer
+}
+
+impl Loss {
+    pub fn value(&self, output: &[f32], target: &[f32]) -> f32 {
+        assert_eq!(output.len(), target.len());
+        let n = output.len() as f32;
+
+        match self {
+            Self::MeanSquaredError => {
+                output.iter().zip(target).map(|(o, t)| (o - t).powi(2)).sum::<f32>() / n
+            }
+            Self::CrossEntropy => {
+                output
+                    .iter()
+                    .zip(target)
+                    .map(|(&o, &t)| {
+                        let o = o.clamp(CROSS_ENTROPY_EPSILON, 1.0 - CROSS_ENTROPY_EPSILON);
+                        -(t * o.ln() + (1.0 - t) * (1.0 - o).ln())
+                    })
+                    .sum::<f32>()
+                    / n
+            }
+        }
+    }
+
+    // d value / d output, written into `gradient`
+    pub fn gradient(&self, output: &[f32], target: &[f32], gradient: &mut [f32]) {
+        assert_eq!(output.len(), target.len());
+        assert_eq!(output.len(), gradient.len());
+        let n = output.len() as f32;
+
+        for ((&o, &t), g) in output.iter().zip(target).zip(gradient.iter_mut()) {
+            *g = match self {
+                Self::MeanSquaredError => 2.0 * (o - t) / n,
+                Self::CrossEntropy => {
+                    let o = o.clamp(CROSS_ENTROPY_EPSILON, 1.0 - CROSS_ENTROPY_EPSILON);
+                    (o - t) / (o * (1.0 - o)) / n
+                }
+            };
+        }
+    }
+}
+
+pub trait Optimizer {
+    fn step(&mut self, params: &mut [f32], gradient: &[f32]);
+}
+
+pub struct Sgd {
+    learning_rate: f32,
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f32) -> Self {
+        assert!(learning_rate > 0.0);
+        Self { learning_rate }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, params: &mut [f32], gradient: &[f32]) {
+        for (param, grad) in params.iter_mut().zip(gradient) {
+            *param -= self.learning_rate * grad;
+        }
+    }
+}
+
+pub struct Adam {
+    learning_rate: f32,
+    beta1: f32,
+    beta2: f32,
+    epsilon: f32,
+    step: i32,
+    m: Vec<f32>, // first moment, sized lazily on the first step
+    v: Vec<f32>, // second moment
+}
+
+impl Adam {
+    pub fn new(learning_rate: f32) -> Self {
+        Self::with_betas(learning_rate, 0.9, 0.999)
+    }
+
+    pub fn with_betas(learning_rate: f32, beta1: f32, beta2: f32) -> Self {
+        assert!(learning_rate > 0.0);
+        assert!((0.0..1.0).contains(&beta1) && (0.0..1.0).contains(&beta2));
+        Self { learning_rate, beta1, beta2, epsilon: 1e-8, step: 0, m: Vec::new(), v: Vec::new() }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, params: &mut [f32], gradient: &[f32]) {
+        if self.m.len() != params.len() {
+            self.m = vec![0.0; params.len()];
+            self.v = vec![0.0; params.len()];
+            self.step = 0;
+        }
+        self.step += 1;
+
+        let m_correction = 1.0 - self.beta1.powi(self.step);
+        let v_correction = 1.0 - self.beta2.powi(self.step);
+
+        for (((param, &grad), m), v) in params
+            .iter_mut()
+            .zip(gradient)
+            .zip(self.m.iter_mut())
+            .zip(self.v.iter_mut())
+        {
+            *m = self.beta1 * *m + (1.0 - self.beta1) * grad;
+            *v = self.beta2 * *v + (1.0 - self.beta2) * grad * grad;
+            *param -= self.learning_rate * (*m / m_correction) / ((*v / v_correction).sqrt() + self.epsilon);
+        }
+    }
+}
+
+// Mini-batch supervised training; `inputs` and `targets` are row-major, one row per sample like `propagate_batch`
+pub struct Trainer<O> {
+    loss: Loss,
+    optimizer: O,
+    batch_size: usize,
+}
+
+impl<O> Trainer<O>
+where
+    O: Optimizer,
+{
+    pub fn new(loss: Loss, optimizer: O, batch_size: usize) -> Self {
+        assert!(batch_size > 0);
+        Self { loss, optimizer, batch_size }
+    }
+
+    // One shuffled pass over the data; returns the mean loss seen during the pass
+    pub fn train_epoch(
+        &mut self,
+        rng: &mut dyn RngCore,
+        network: &mut Network,
+        inputs: &[f32],
+        targets: &[f32],
+    ) -> f32 {
+        let (input_size, output_size) = (network.input_size(), network.output_size());
+        assert_eq!(inputs.len() % input_size, 0);
+        assert_eq!(targets.len() % output_size, 0);
+        let samples = inputs.len() / input_size;
+        assert_eq!(samples, targets.len() / output_size);
+        assert!(samples > 0);
+
+        let mut order: Vec<usize> = (0..samples).collect();
+        order.shuffle(rng);
+
+        let mut params = network.get_params();
+        let mut gradient = vec![0.0; params.len()];
+        let mut total_loss = 0.0;
+
+        for batch in order.chunks(self.batch_size) {
+            gradient.fill(0.0);
+            for &sample in batch {
+                total_loss += network.accumulate_gradient(
+                    &inputs[sample * input_size..(sample + 1) * input_size],
+                    &targets[sample * output_size..(sample + 1) * output_size],
+                    self.loss,
+                    &mut gradient,
+                );
+            }
+
+            let scale = 1.0 / batch.len() as f32;
+            gradient.iter_mut().for_each(|grad| *grad *= scale);
+            self.optimizer.step(&mut params, &gradient);
+            network.set_params(&params);
+        }
+
+        total_loss / samples as f32
+    }
+}
+
+struct LayerCache {
+    inputs: Vec<f32>,
+    sums: Vec<f32>, // one per row, so gated layers carry their gate sums after the candidates
+    outputs: Vec<f32>,
+}
+
+impl Network {
+    // Backprop for one sample, added onto `gradient` (laid out like `get_params`); returns the sample's loss.
+    // Recurrent layers are differentiated as if their hidden state were zero, i.e. one step from a fresh `NetworkState`.
+    pub fn accumulate_gradient(&self, input: &[f32], target: &[f32], loss: Loss, gradient: &mut [f32]) -> f32 {
+        assert_eq!(input.len(), self.input_size());
+        assert_eq!(target.len(), self.output_size());
+        assert_eq!(gradient.len(), self.layers.iter().map(|layer| layer.params.len()).sum::<usize>());
+
+        let mut caches: Vec<LayerCache> = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            let inputs = caches.last().map_or(input, |cache| &cache.outputs).to_vec();
+            let sums = (0..layer.output_size * layer.kind.gates())
+                .map(|row| layer.weighted_sum(row, &inputs, None))
+                .collect();
+            let mut outputs = vec![0.0; layer.output_size];
+            layer.propagate_into(&inputs, None, &mut outputs);
+            caches.push(LayerCache { inputs, sums, outputs });
+        }
+
+        let output = &caches.last().unwrap().outputs;
+        let value = loss.value(output, target);
+        let mut delta = vec![0.0; output.len()];
+        loss.gradient(output, target, &mut delta);
+
+        let mut end = gradient.len();
+        for (layer, cache) in self.layers.iter().zip(&caches).rev() {
+            let start = end - layer.params.len();
+            let layer_gradient = &mut gradient[start..end];
+            end = start;
+
+            let mut previous_delta = vec![0.0; layer.input_size];
+
+            for (neuron, &output_delta) in delta.iter().enumerate() {
+                let candidate = layer.activation.derivative(cache.sums[neuron]);
+                let row_deltas = match layer.kind {
+                    LayerKind::Dense | LayerKind::Elman => [(neuron, output_delta * candidate), (0, 0.0)],
+                    LayerKind::Gated => {
+                        // with no previous state the output is simply gate * candidate
+                        let gate_row = layer.output_size + neuron;
+                        let gate = Activation::Sigmoid.apply(cache.sums[gate_row]);
+                        let proposal = layer.activation.apply(cache.sums[neuron]);
+                        [
+                            (neuron, output_delta * gate * candidate),
+                            (gate_row, output_delta * proposal * gate * (1.0 - gate)),
+                        ]
+                    }
+                };
+
+                for (row, row_delta) in row_deltas {
+                    if row_delta == 0.0 {
+                        continue;
+                    }
+                    let grads = &mut layer_gradient[layer.row_range(row)];
+                    let weights = &layer.row(row)[1..=layer.input_size];
+
+                    grads[0] += row_delta;
+                    for (i, &input) in cache.inputs.iter().enumerate() {
+                        grads[1 + i] += row_delta * input;
+                        previous_delta[i] += row_delta * weights[i];
+                    }
+                }
+            }
+
+            delta = previous_delta;
+        }
+
+        value
+    }
+
+    pub(crate) fn set_params(&mut self, params: &[f32]) {
+        let mut params = params.iter();
+        for layer in &mut self.layers {
+            for (param, &value) in layer.params.iter_mut().zip(&mut params) {
+                *param = value;
+            }
+        }
+        assert!(params.next().is_none(), "got too many weights");
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network(rng: &mut ChaCha8Rng, kind: LayerKind, output: Activation) -> Network {
+        Network::random(rng, &[
+            LayerTopology {num_neuron: 2, activation: Activation::Relu, kind: LayerKind::Dense},
+            LayerTopology {num_neuron: 3, activation: Activation::Tanh, kind},
+            LayerTopology {num_neuron: 2, activation: output, kind: LayerKind::Dense},
+        ])
+    }
+
+    fn check_gradient(kind: LayerKind, output: Activation, loss: Loss, target: [f32; 2]) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = network(&mut rng, kind, output);
+        let input = [0.4, -0.7];
+
+        let params = network.get_params();
+        let mut gradient = vec![0.0; params.len()];
+        network.accumulate_gradient(&input, &target, loss, &mut gradient);
+
+        let h = 1e-3;
+        for index in 0..params.len() {
+            let mut shifted = params.clone();
+            shifted[index] += h;
+            network.set_params(&shifted);
+            let up = loss.value(&network.propagate(input.to_vec()), &target);
+            shifted[index] -= 2.0 * h;
+            network.set_params(&shifted);
+            let down = loss.value(&network.propagate(input.to_vec()), &target);
+
+            assert_relative_eq!(gradient[index], (up - down) / (2.0 * h), epsilon = 2e-3);
+        }
+    }
+
+    #[test]
+    fn gradient_matches_finite_difference() {
+        check_gradient(LayerKind::Dense, Activation::Identity, Loss::MeanSquaredError, [0.5, -0.3]);
+        check_gradient(LayerKind::Elman, Activation::Tanh, Loss::MeanSquaredError, [0.5, -0.3]);
+        check_gradient(LayerKind::Gated, Activation::Sigmoid, Loss::CrossEntropy, [1.0, 0.0]);
+    }
+
+    fn fit(optimizer: impl Optimizer) -> (f32, f32) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = network(&mut rng, LayerKind::Dense, Activation::Identity);
+        let inputs = [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.5, 0.5];
+        let targets: Vec<f32> = inputs
+            .chunks(2)
+            .flat_map(|input| [input[0] - input[1], 0.5 * (input[0] + input[1])])
+            .collect();
+
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, optimizer, 2);
+        let first = trainer.train_epoch(&mut rng, &mut network, &inputs, &targets);
+        let mut last = first;
+        for _ in 0..500 {
+            last = trainer.train_epoch(&mut rng, &mut network, &inputs, &targets);
+        }
+        (first, last)
+    }
+
+    #[test]
+    fn sgd_reduces_loss() {
+        let (first, last) = fit(Sgd::new(0.05));
+        assert!(last < first * 0.1, "{} -> {}", first, last);
+    }
+
+    #[test]
+    fn adam_reduces_loss() {
+        let (first, last) = fit(Adam::new(0.01));
+        assert!(last < first * 0.1, "{} -> {}", first, last);
+    }
+}